@@ -1,5 +1,6 @@
 use crate::algebraic_structure::{Element, HasAdd, HasMul, HasRepresentation, HasSub};
-use crate::integers::integer_computations::{extended_euclidean_ordered, extended_euclidean_to_integers, pow_rug};
+use crate::integers::integer_computations::{extended_euclidean_to_integers, extended_gcd, pow_rug, BarrettContext};
+use crate::integers::prime::certificate::PrimalityCertificate;
 use rug::ops::SubFrom;
 use rug::{integer::IsPrime, Complete, Integer};
 use std::sync::Arc;
@@ -11,6 +12,9 @@ use super::HasDiv;
 pub struct FiniteField {
     // This struct will only consider finite fields isomorphic to Z_p for p prime.
     size: Integer,
+    // Opt-in through with_barrett_reduction, as the precomputation only pays off for repeated
+    // multiplications in the same field.
+    barrett: Option<BarrettContext>,
 }
 
 
@@ -23,16 +27,25 @@ impl HasRepresentation for FiniteField {
 
 impl HasMul for FiniteField {
     fn mul(&self, a: &Element<FiniteField>, b: &Element<FiniteField>) -> Element<FiniteField> {
+        let product = (a.get_rep() * b.get_rep()).complete();
+        let representation = match &self.barrett {
+            Some(barrett) => barrett.reduce(&product),
+            None => product % self.mod_num(),
+        };
         Element::new(
             a.get_outer_structure(),
-            (a.get_rep() * b.get_rep()).complete() % self.mod_num()
+            representation
         )
     }
 
     fn pow(&self, a: &Element<Self>, b: &Integer) -> Element<Self> {
+        let representation = match &self.barrett {
+            Some(barrett) => barrett.pow(a.get_rep(), b),
+            None => pow_rug(a.get_rep(), b, self.mod_num()),
+        };
         Element::new(
             a.get_outer_structure(),
-            pow_rug(a.get_rep(), b, self.mod_num())
+            representation
         )
     }
 }
@@ -53,10 +66,10 @@ impl HasDiv for FiniteField {
         if a.get_rep().is_zero() {
             panic!("Zero Division");
         }
-        let (_, _, y) = extended_euclidean_ordered(self.mod_num(), a.get_rep());
+        let (_, x, _) = extended_gcd(a.get_rep(), self.mod_num());
         Element::new(
             a.get_outer_structure(),
-            y
+            x
         )
     }
 }
@@ -79,12 +92,24 @@ impl FiniteField {
         if size.is_probably_prime(30) != IsPrime::No {
             Some(FiniteField {
                 size,
+                barrett: None,
             })
         } else {
             None
         }
     }
 
+    // Only accepts a size that is proven prime, instead of the probable primes of new().
+    pub fn from_certificate(certificate: &PrimalityCertificate) -> Option<FiniteField> {
+        if !certificate.verify() {
+            return None
+        }
+        Some(FiniteField {
+            size: certificate.get_prime().clone(),
+            barrett: None,
+        })
+    }
+
     pub fn one(self) -> Element<FiniteField> {
         Element {
             outer_structure: Arc::new(self),
@@ -99,6 +124,15 @@ impl FiniteField {
         }
     }
 
+    pub fn with_barrett_reduction(mut self) -> FiniteField {
+        self.barrett = BarrettContext::new(self.size.clone());
+        self
+    }
+
+    pub fn uses_barrett_reduction(&self) -> bool {
+        self.barrett.is_some()
+    }
+
     pub fn get_size(&self) -> Integer {
         self.size.clone()
     }
@@ -195,10 +229,10 @@ impl MultiplicativeGroup {
 
 impl HasDiv for MultiplicativeGroup {
     fn mul_inv(&self, a: &Element<Self>) -> Element<Self> {
-        let (_, _, y) = extended_euclidean_ordered(self.mod_num(), a.get_rep());
+        let (_, x, _) = extended_gcd(a.get_rep(), self.mod_num());
         Element::new(
             a.get_outer_structure(),
-            y
+            x
         )
     }
 }
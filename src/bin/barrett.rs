@@ -0,0 +1,91 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use beralg::algebraic_structure::{finite_field::FiniteField, Element};
+use beralg::integers::integer_computations::{pow_rug, BarrettContext};
+use beralg::integers::prime::find_prime_with_bit_length;
+use beralg::random::randint_bits;
+use rug::{Complete, Integer};
+
+
+fn time_reductions(p: &Integer, products: &[Integer]) -> (Duration, Duration) {
+    let barrett = BarrettContext::new(p.clone()).expect("Primes are positive.");
+
+    let now = Instant::now();
+    for x in products {
+        x.modulo_ref(p).complete();
+    }
+    let elapsed_modulo = now.elapsed();
+
+    let now = Instant::now();
+    for x in products {
+        barrett.reduce(x);
+    }
+    let elapsed_barrett = now.elapsed();
+
+    (elapsed_modulo, elapsed_barrett)
+}
+
+
+fn time_pow(p: &Integer, a: &Integer, b: &Integer, n: usize) -> (Duration, Duration) {
+    let barrett = BarrettContext::new(p.clone()).expect("Primes are positive.");
+
+    let now = Instant::now();
+    for _ in 0..n {
+        pow_rug(a, b, p);
+    }
+    let elapsed_pow_rug = now.elapsed();
+
+    let now = Instant::now();
+    for _ in 0..n {
+        barrett.pow(a, b);
+    }
+    let elapsed_barrett = now.elapsed();
+
+    (elapsed_pow_rug, elapsed_barrett)
+}
+
+
+fn time_field_mul(p: &Integer, n: usize) -> (Duration, Duration) {
+    let f = Arc::new(FiniteField::new(p.clone()).unwrap());
+    let f_barrett = Arc::new(FiniteField::new(p.clone()).unwrap().with_barrett_reduction());
+    let a = randint_bits(p.significant_bits() as usize - 1);
+
+    let mut elem = Element::new(f.clone(), a.clone());
+    let now = Instant::now();
+    for _ in 0..n {
+        elem = elem.mul_ref(&elem);
+    }
+    let elapsed_mul = now.elapsed();
+
+    let mut elem = Element::new(f_barrett.clone(), a);
+    let now = Instant::now();
+    for _ in 0..n {
+        elem = elem.mul_ref(&elem);
+    }
+    let elapsed_barrett = now.elapsed();
+
+    (elapsed_mul, elapsed_barrett)
+}
+
+
+fn main() {
+    let t = 0;
+    let n = 1000;
+
+    for bits in [64, 256, 1024, 2048, 4096] {
+        let p = find_prime_with_bit_length(bits, t);
+        let products: Vec<Integer> = (0..n).map(|_| randint_bits(2*bits - 1)).collect();
+        let a = randint_bits(bits - 1);
+        let b = randint_bits(bits);
+
+        let (modulo, barrett) = time_reductions(&p, &products);
+        println!("{} bits, {} reductions:  modulo {:?},  barrett {:?}", bits, n, modulo, barrett);
+
+        let (pow, barrett) = time_pow(&p, &a, &b, 10);
+        println!("{} bits, 10 exponentiations:  pow_rug {:?},  barrett {:?}", bits, pow, barrett);
+
+        let (mul, barrett) = time_field_mul(&p, n);
+        println!("{} bits, {} field multiplications:  modulo {:?},  barrett {:?}", bits, n, mul, barrett);
+        println!();
+    }
+}
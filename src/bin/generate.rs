@@ -1,4 +1,5 @@
 use beralg::factor::file_handler::choose_primes;
+use beralg::integers::sieve::{nth_prime, primes_in_range};
 use rand::{thread_rng, Rng};
 use rug::integer::IsPrime;
 use rug::{Complete, Integer};
@@ -19,16 +20,13 @@ fn generate_primes() {
         .unwrap();
     
     
-    let mut p: Integer = Integer::ONE.clone();
     let number_of_primes = 100;
     let space_of_primes = 10000;
+    let largest = nth_prime(number_of_primes*space_of_primes).expect("Fits in a u64");
 
     println!("Starting prime generating...");
-    for _ in 0..number_of_primes {
-        for _ in 0..space_of_primes {
-            p.next_prime_mut();
-        }
-        if let Err(e) = writeln!(file, "{}", &p) {
+    for p in primes_in_range(2, largest + 1).skip(space_of_primes as usize - 1).step_by(space_of_primes as usize) {
+        if let Err(e) = writeln!(file, "{}", p) {
             eprintln!("Couldn't write to file: {}", e)
         }
     }
@@ -73,14 +71,11 @@ fn generate_small_primes(n: usize) {
         .open("small-primes")
         .unwrap();
 
-    let mut p: Integer = Integer::from(2);
-    
     println!("Generating list of small primes...");
-    while p < n {
-        if let Err(e) = writeln!(file, "{}", &p) {
+    for p in primes_in_range(2, n as u64) {
+        if let Err(e) = writeln!(file, "{}", p) {
             eprintln!("Couldn't write to file: {}", e);
         }
-        p.next_prime_mut();
     }
 }
 
@@ -1,8 +1,7 @@
 #![allow(dead_code)]
 use std::{sync::Arc, time::Duration};
 use std::time::Instant;
-use beralg::{algebraic_structure::{finite_field::FiniteField, Element}, integers::integer_computations::naive_pow};
-use rug::integer::IsPrime;
+use beralg::{algebraic_structure::{finite_field::FiniteField, Element}, integers::{integer_computations::naive_pow, prime::lucas_lehmer_is_prime}};
 use rug::ops::PowAssign;
 use rug::Integer;
 use plotters::prelude::*;
@@ -58,7 +57,7 @@ fn plot_timing_naive_square(n: usize, m: usize) -> Result<(), Box<dyn std::error
     let mut p = Integer::from(2);
     p.pow_assign(127);
     p = p-1;
-    assert!(p.is_probably_prime(20) != IsPrime::No);
+    assert!(lucas_lehmer_is_prime(127));
     // let mut rng = RandState::new();
     let mut naive_vec: Vec<(u128, u128)> = Vec::new();
     let mut square_vec: Vec<(u128, u128)> = Vec::new();
@@ -158,7 +157,7 @@ fn plot_timing_naive(n: usize, m: usize) -> Result<(), Box<dyn std::error::Error
     let mut q = Integer::from(2);
     q.pow_assign(127);
     q = q-1;
-    assert!(q.is_probably_prime(20) != IsPrime::No);
+    assert!(lucas_lehmer_is_prime(127));
     // let mut rng = RandState::new();
     let mut naive_vec: Vec<(u128, u128)> = Vec::new();
     let mut max_time_naive = 0;
@@ -236,7 +235,7 @@ fn plot_timing_square(n: usize, m: usize) -> Result<(), Box<dyn std::error::Erro
     let mut q = Integer::from(2);
     q.pow_assign(127);
     q = q-1;
-    assert!(q.is_probably_prime(20) != IsPrime::No);
+    assert!(lucas_lehmer_is_prime(127));
     let mut p = Integer::ONE.clone();
     // let mut rng = RandState::new();
     let mut square_vec: Vec<(u64, u64)> = Vec::new();
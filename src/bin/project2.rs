@@ -1,4 +1,5 @@
 #![allow(dead_code)]
+use beralg::integers::prime::table::small_primes;
 use beralg::integers::prime::{find_prime_in_interval_with_sieving, find_prime_with_bit_length, find_prime_with_bit_length_using_sieving, find_prime_with_bit_length_using_trial_division, is_likely_prime_with_trial_division, rabin_miller_is_prime};
 use beralg::random::{randint_bits, randint_bits_odd};
 use rug::Integer;
@@ -43,14 +44,11 @@ fn expected_candidates(bits: usize) -> f64 {
 
 
 fn expected_candidates_with_filtration(bits: usize, bound: usize) -> f64 {
-    let small_primes = fs::File::open("small-primes").expect("small-primes file should have been generated");
-    let reader = BufReader::new(small_primes);
     let mut prod: f64 = 1.;
     
-    for prime in reader.lines() {
-        let p: Integer = Integer::from_str(&prime.unwrap()).expect("All entries of small-primes should be integers.");
-        if p < bound {
-            prod *= (p.to_f64()-1.)/p.to_f64();
+    for &p in small_primes().up_to(bound) {
+        if (p as usize) < bound {
+            prod *= (p as f64 - 1.)/(p as f64);
         }
     }
 
@@ -61,7 +59,7 @@ fn expected_candidates_with_filtration(bits: usize, bound: usize) -> f64 {
 fn time_finding_primes() {
     let bits = 300;
     let loops = 80;
-    let t = 20;
+    let t = 0;
 
     let mut number_of_primes_for_trial_division = 0;
     let increase = 50;
@@ -131,7 +129,7 @@ fn find_good_number_of_precomputed_primes(bits: usize) {
     let increment = 50;
     let stop = 1000;
     let loops = 40;
-    let t = 30;
+    let t = 0;
     let security = 5;
 
     generate_list_of_composite_until_prime(bits, false);
@@ -163,7 +161,7 @@ fn find_good_number_of_primes_for_sieving(bits: usize) {
     let start = 2000;
     let stop = 17000;
     let loops = 40;
-    let t = 30;
+    let t = 0;
     let number_of_generated = 40;
 
     let mut bound = start;
@@ -200,7 +198,7 @@ fn time_finding_primes_trial_division_vs_sieving() {
     let loops = 1000;
     let bound_td = 150;
     let bound_sieving = 12000;
-    let t = 30;
+    let t = 0;
 
     let now = Instant::now();
     for _ in 0..loops {
@@ -5,7 +5,7 @@ fn main() {
     let n = Integer::from(2003u64*1064u64*3539u64*8539u64);
     let number_of_relations = 200;
 
-    let factors = factorization_by_random_squares(&n, number_of_relations, 0);
+    let factors = factorization_by_random_squares(&n, number_of_relations);
     println!("{} factors into: ", &n);
     for factor in factors {
         print!("{}, ", factor);
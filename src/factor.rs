@@ -1,2 +1,3 @@
 pub mod file_handler;
+pub mod quadratic_sieve;
 pub mod random_squares;
@@ -0,0 +1,524 @@
+use std::collections::HashMap;
+use rug::{ops::Pow, Complete, Integer};
+use crate::integers::arith::jacobi;
+use crate::integers::prime::baillie_psw_is_prime;
+use crate::integers::prime::table::small_primes;
+use crate::integers::sieve::next_prime;
+use super::random_squares::{factor_from_squares, find_all_squares_by_relations, merge_tuples};
+
+
+// -1 is recorded as the factor 1 of a negative Q(x). The parity matrix treats it as any other
+// prime, so that the chosen Q(x) have an even number of negative ones, and it contributes
+// nothing to the square root.
+const MINUS_ONE: u64 = 1;
+// Length of the part of the interval that is sieved at once, small enough to stay in cache.
+const BLOCK_SIZE: usize = 1 << 16;
+// Every polynomial is sieved over [-M, M) for M this many blocks.
+const BLOCKS_PER_SIDE: usize = 2;
+// The sieve threshold is recomputed for every chunk of this many positions.
+const CHUNK_SIZE: usize = 1 << 10;
+// Primes below this are not sieved, as they cost the most time for the fewest bits, which is
+// made up for by a lower threshold.
+const SMALL_PRIME_BOUND: u64 = 30;
+// Bits of the threshold given up for the unsieved small primes and rounding of the logarithms.
+const SMALL_PRIME_SLACK: f64 = 16.;
+// Relations beyond the size of the factor base, each giving about one more dependency.
+const EXTRA_RELATIONS: usize = 16;
+// Remaining cofactors below this times the largest prime of the factor base are kept as partial
+// relations with one large prime, two of which with the same large prime give a full relation.
+const LARGE_PRIME_MULTIPLIER: u64 = 64;
+
+
+// A prime of the factor base with a square root of n modulo p and its log2 rounded as added by
+// the sieve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FactorBasePrime {
+    pub p: u64,
+    pub sqrt: u64,
+    log: u8,
+}
+
+
+fn pow_mod(mut a: u64, mut b: u64, p: u64) -> u64 {
+    let mut result = 1 % p;
+    a %= p;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = (result as u128*a as u128 % p as u128) as u64;
+        }
+        a = (a as u128*a as u128 % p as u128) as u64;
+        b >>= 1;
+    }
+    result
+}
+
+
+// a^(-1) modulo p for a coprime to p by the extended Euclidean algorithm, which is far cheaper
+// than a^(p-2) when it has to be done for every prime of the factor base and every polynomial.
+fn inverse_mod(a: u64, p: u64) -> u64 {
+    let (mut r_0, mut r_1) = (p as i64, (a % p) as i64);
+    let (mut s_0, mut s_1) = (0i64, 1i64);
+    while r_1 != 0 {
+        let quotient = r_0/r_1;
+        (r_0, r_1) = (r_1, r_0 - quotient*r_1);
+        (s_0, s_1) = (s_1, s_0 - quotient*s_1);
+    }
+    s_0.rem_euclid(p as i64) as u64
+}
+
+
+// Square root of a quadratic residue a modulo an odd prime p by Tonelli-Shanks: with
+// p - 1 = 2^s q and z a non-residue, r = a^((q+1)/2) is corrected by powers of z^q until
+// a^q, which lies in the subgroup of order 2^s, is reduced to 1.
+fn sqrt_mod(a: u64, p: u64) -> u64 {
+    let s = (p - 1).trailing_zeros();
+    let q = (p - 1) >> s;
+    let z = (2..p).find(|z| pow_mod(*z, (p - 1)/2, p) == p - 1).expect("Half of the units are non-residues");
+
+    let mul = |x: u64, y: u64| (x as u128*y as u128 % p as u128) as u64;
+    let mut m = s;
+    let mut c = pow_mod(z, q, p);
+    let mut t = pow_mod(a, q, p);
+    let mut r = pow_mod(a, q.div_ceil(2), p);
+    while t != 1 {
+        let mut i = 0;
+        let mut t_power = t;
+        while t_power != 1 {
+            t_power = mul(t_power, t_power);
+            i += 1;
+        }
+        let b = pow_mod(c, 1u64 << (m - i - 1), p);
+        m = i;
+        c = mul(b, b);
+        t = mul(t, c);
+        r = mul(r, b);
+    }
+    r
+}
+
+
+// The factor base of odd n: 2 and the first odd primes p with (n/p) = 1, as only those divide
+// some Q(x). Primes dividing n have (n/p) = 0 and are left out, so they should be removed from n
+// first.
+pub fn factor_base(n: &Integer, size: usize) -> Vec<FactorBasePrime> {
+    assert!(n.is_odd(), "The factor base is only built for odd n");
+    let mut base: Vec<FactorBasePrime> = Vec::with_capacity(size);
+    let mut bound = 1 << 12;
+    while base.len() < size {
+        base.clear();
+        for &p in small_primes().up_to(bound) {
+            let p = p as u64;
+            let n_mod_p = n.mod_u(p as u32) as u64;
+            let sqrt = if p == 2 {
+                1
+            } else if n_mod_p != 0 && pow_mod(n_mod_p, (p - 1)/2, p) == 1 {
+                sqrt_mod(n_mod_p, p)
+            } else {
+                continue;
+            };
+            base.push(FactorBasePrime { p, sqrt, log: (p as f64).log2().round() as u8 });
+            if base.len() == size {
+                break;
+            }
+        }
+        bound *= 2;
+    }
+    base
+}
+
+
+// Size of the factor base for n, interpolated from sizes that work well for numbers of the
+// given decimal digits.
+pub fn quadratic_sieve_factor_base_size(n: &Integer) -> usize {
+    const SIZES: [(f64, f64); 8] = [
+        (10., 30.), (20., 100.), (30., 250.), (40., 650.),
+        (50., 1500.), (60., 3200.), (70., 6500.), (80., 12000.),
+    ];
+    let digits = n.significant_bits() as f64*2f64.log10();
+    let (low, high) = SIZES
+        .windows(2)
+        .map(|w| (w[0], w[1]))
+        .find(|(_, high)| digits <= high.0)
+        .unwrap_or((SIZES[6], SIZES[7]));
+    let size = low.1 + (digits - low.0)*(high.1 - low.1)/(high.0 - low.0);
+    size.clamp(SIZES[0].1, 2.*SIZES[7].1) as usize
+}
+
+
+// g(x) = ((ax + b)^2 - n)/a for a = q^2 and b^2 = n (mod a), so that for t = (ax + b)/q,
+//      t^2 = g(x) (mod n),
+// and the values near x = 0 are about M sqrt(n/2) on [-M, M) when a is about sqrt(2n)/M. The
+// first polynomial is Q(x) = (x + m)^2 - n for m = floor(sqrt(n)), i.e. q = 1 and b = m, with
+// values about 2|x|sqrt(n). Each further one, as in Montgomery's multiple polynomial variant,
+// starts afresh from small values where the first would have grown large.
+struct Polynomial {
+    a: Integer,
+    b: Integer,
+    c: Integer,
+    q_inverse: Integer,
+}
+
+
+impl Polynomial {
+    fn first(n: &Integer) -> Polynomial {
+        let m: Integer = n.sqrt_ref().complete();
+        let c: Integer = m.square_ref().complete() - n;
+        Polynomial { a: Integer::from(1), b: m, c, q_inverse: Integer::from(1) }
+    }
+
+
+    // For a prime q = 3 (mod 4) with (n/q) = 1, s = n^((q+1)/4) is a square root of n modulo q,
+    // which Hensel lifting takes to b = s + kq with k = (n - s^2)/q (2s)^(-1) (mod q).
+    fn with_prime(n: &Integer, q: &Integer) -> Polynomial {
+        let s: Integer = n.pow_mod_ref(&(q + 1u32).complete().div_exact(&Integer::from(4)), q).expect("q is positive").into();
+        let two_s_inverse: Integer = (&s << 1u32).complete().invert(q).expect("q does not divide 2n");
+        let k: Integer = ((n - s.square_ref().complete()).div_exact(q)*two_s_inverse).modulo(q);
+        let a: Integer = q.square_ref().complete();
+        let b: Integer = s + k*q;
+        let c: Integer = (b.square_ref().complete() - n).div_exact(&a);
+        let q_inverse: Integer = q.invert_ref(n).expect("n has no factor this small").into();
+        Polynomial { a, b, c, q_inverse }
+    }
+
+
+    // x with a x + b = +-sqrt(n) (mod p), where p does not divide a.
+    fn roots(&self, prime: &FactorBasePrime) -> [u64; 2] {
+        let p = prime.p;
+        let b = self.b.mod_u(p as u32) as u64;
+        if p == 2 {
+            // n is odd, so ax + b must be as well.
+            let root = (1 + b) % 2;
+            return [root, root];
+        }
+        let a_inverse = inverse_mod(self.a.mod_u(p as u32) as u64, p);
+        let mul = |x: u64, y: u64| (x as u128*y as u128 % p as u128) as u64;
+        [mul((prime.sqrt + p - b) % p, a_inverse), mul((2*p - prime.sqrt - b) % p, a_inverse)]
+    }
+
+
+    fn value(&self, x: i64) -> Integer {
+        let x = Integer::from(x);
+        (&self.a*&x).complete()*&x + (&self.b*&x).complete()*2u32 + &self.c
+    }
+
+
+    fn log2_abs_value(&self, x: f64) -> f64 {
+        (self.a.to_f64()*x*x + 2.*self.b.to_f64()*x + self.c.to_f64()).abs().max(1.).log2()
+    }
+
+
+    fn square_root(&self, x: i64, n: &Integer) -> Integer {
+        (((&self.a*Integer::from(x)) + &self.b)*&self.q_inverse).modulo(n)
+    }
+}
+
+
+// Factorization of |g(x)| over the factor base, where only the primes with x at one of their
+// roots divide it, leaving the cofactor in g.
+fn factor_over_base(g: &mut Integer, x: i64, base: &[FactorBasePrime], roots: &[[u64; 2]]) -> Vec<(u64, u64)> {
+    let mut factors: Vec<(u64, u64)> = Vec::new();
+    if *g < 0 {
+        factors.push((MINUS_ONE, 1));
+        *g = -g.clone();
+    }
+    for (prime, roots) in base.iter().zip(roots) {
+        let x_mod_p = x.rem_euclid(prime.p as i64) as u64;
+        if x_mod_p != roots[0] && x_mod_p != roots[1] {
+            continue;
+        }
+        let mut exponent = 0;
+        while g.is_divisible_u(prime.p as u32) {
+            *g /= prime.p as u32;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            factors.push((prime.p, exponent));
+        }
+    }
+    factors
+}
+
+
+// Adds log2 p at every position i of the block with start + i at a root of p.
+fn sieve_block(sieve: &mut [u8], start: i64, base: &[FactorBasePrime], roots: &[[u64; 2]]) {
+    sieve.fill(0);
+    for (prime, roots) in base.iter().zip(roots).filter(|(prime, _)| prime.p >= SMALL_PRIME_BOUND) {
+        let p = prime.p as usize;
+        let distinct: &[u64] = if roots[0] == roots[1] { &roots[..1] } else { roots };
+        for root in distinct {
+            let mut i = (*root as i64 - start).rem_euclid(p as i64) as usize;
+            while i < sieve.len() {
+                sieve[i] = sieve[i].saturating_add(prime.log);
+                i += p;
+            }
+        }
+    }
+}
+
+
+// The primes q = 3 (mod 4) with (n/q) = 1 for the polynomials after the first, from about
+// sqrt(sqrt(2n)/M) upwards but above the factor base, so that a = q^2 is coprime to it.
+fn polynomial_primes(n: &Integer, largest: u64) -> impl Iterator<Item = Integer> + '_ {
+    let half_width = (BLOCKS_PER_SIDE*BLOCK_SIZE) as u32;
+    let target: Integer = ((n << 1u32).complete().sqrt()/half_width).sqrt();
+    let mut q: Integer = target.max(Integer::from(largest));
+    std::iter::from_fn(move || loop {
+        q = next_prime(&q);
+        if q.mod_u(4) == 3 && jacobi(n, &q) == 1 {
+            return Some(q.clone());
+        }
+    })
+}
+
+
+// Relations t^2 = prod p^e (mod n) from the values g(x) of one polynomial after another over
+// [-M, M), starting with Q(x) = (x + floor(sqrt(n)))^2 - n on an interval around sqrt(n). A
+// relation is kept if g(x) is smooth over the factor base, or if it is so up to one large prime
+// that another g(x) shares. Positions whose sieved logarithms come close to log2 |g(x)| are trial
+// divided, and relations are collected until there are relations_needed.
+pub fn find_relations_by_quadratic_sieve(n: &Integer, base: &[FactorBasePrime], relations_needed: usize) -> HashMap<Integer, Vec<(u64, u64)>> {
+    let largest = base.last().expect("The factor base is not empty").p;
+    let large_prime_bound = largest.saturating_mul(LARGE_PRIME_MULTIPLIER).min(largest.saturating_mul(largest));
+    let slack = (large_prime_bound as f64).log2() + SMALL_PRIME_SLACK;
+    let half_width = (BLOCKS_PER_SIDE*BLOCK_SIZE) as i64;
+
+    let mut relations: HashMap<Integer, Vec<(u64, u64)>> = HashMap::with_capacity(relations_needed);
+    let mut partials: HashMap<u64, (Integer, Vec<(u64, u64)>)> = HashMap::new();
+    let mut sieve: Vec<u8> = vec![0; BLOCK_SIZE];
+
+    let polynomials = std::iter::once(Polynomial::first(n))
+        .chain(polynomial_primes(n, largest).map(|q| Polynomial::with_prime(n, &q)));
+    for polynomial in polynomials {
+        let roots: Vec<[u64; 2]> = base.iter().map(|prime| polynomial.roots(prime)).collect();
+
+        for start in (-half_width..half_width).step_by(BLOCK_SIZE) {
+            sieve_block(&mut sieve, start, base, &roots);
+
+            for (chunk_index, chunk) in sieve.chunks(CHUNK_SIZE).enumerate() {
+                let chunk_start = start + (chunk_index*CHUNK_SIZE) as i64;
+                let largest_log = polynomial.log2_abs_value(chunk_start as f64)
+                    .max(polynomial.log2_abs_value((chunk_start + CHUNK_SIZE as i64) as f64));
+                let threshold = (largest_log - slack).max(0.);
+
+                for (i, _) in chunk.iter().enumerate().filter(|(_, v)| **v as f64 >= threshold) {
+                    let x = chunk_start + i as i64;
+                    let mut g = polynomial.value(x);
+                    if g == 0 {
+                        continue;
+                    }
+                    let factors = factor_over_base(&mut g, x, base, &roots);
+                    let t = polynomial.square_root(x, n);
+
+                    if g == 1 {
+                        relations.insert(t, factors);
+                    } else if let Some(large) = g.to_u64().filter(|l| *l < large_prime_bound) {
+                        match partials.remove(&large) {
+                            Some((other_t, other_factors)) => {
+                                let combined_t: Integer = (t*other_t) % n;
+                                let mut combined: Vec<(u64, u64)> = factors;
+                                combined.extend(other_factors);
+                                combined.push((large, 2));
+                                relations.insert(combined_t, merge_tuples(&combined));
+                            }
+                            None => {
+                                partials.insert(large, (t, factors));
+                            }
+                        }
+                    }
+                    if relations.len() >= relations_needed {
+                        return relations;
+                    }
+                }
+            }
+        }
+    }
+    unreachable!("There are infinitely many polynomials")
+}
+
+
+// A factor 1 < d < n of an odd composite n that is not a perfect power and has no factor in
+// the factor base. Every dependency of the relations gives a factor with probability about 1/2,
+// and if none does more are collected.
+fn find_factor_by_sieving(n: &Integer) -> Integer {
+    let base = factor_base(n, quadratic_sieve_factor_base_size(n));
+    let mut relations_needed = base.len() + 1 + EXTRA_RELATIONS;
+    loop {
+        let relations = find_relations_by_quadratic_sieve(n, &base, relations_needed);
+        for squares in find_all_squares_by_relations(&relations) {
+            let factor = factor_from_squares(n, &relations, &squares);
+            if factor != 1 && factor != *n {
+                return factor;
+            }
+        }
+        relations_needed += EXTRA_RELATIONS;
+    }
+}
+
+
+// A factor 1 < d < n of a composite n found by the quadratic sieve. Small factors and perfect
+// powers, for which the sieve would not work, are found directly.
+pub fn find_factor_by_quadratic_sieve(n: &Integer) -> Integer {
+    assert!(*n > 3 && !baillie_psw_is_prime(n), "Only composite numbers have a proper factor");
+    for &p in small_primes().up_to(1 << 16) {
+        if n.is_divisible_u(p) {
+            return Integer::from(p);
+        }
+    }
+    if n.is_perfect_power() {
+        for k in (2..=n.significant_bits()).rev() {
+            let root: Integer = n.root_ref(k).complete();
+            if root.clone().pow(k) == *n {
+                return root;
+            }
+        }
+    }
+    find_factor_by_sieving(n)
+}
+
+
+// The prime factors of n > 0 with multiplicity in increasing order.
+pub fn factorization_by_quadratic_sieve(n: &Integer) -> Vec<Integer> {
+    assert!(*n > 0, "Only positive numbers are factored");
+    let mut factors: Vec<Integer> = Vec::new();
+    let mut stack: Vec<Integer> = vec![n.clone()];
+    while let Some(m) = stack.pop() {
+        if m == 1 {
+            continue;
+        }
+        if m <= 3 || baillie_psw_is_prime(&m) {
+            factors.push(m);
+            continue;
+        }
+        let d = find_factor_by_quadratic_sieve(&m);
+        stack.push((&m/&d).complete());
+        stack.push(d);
+    }
+    factors.sort();
+    factors
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rug::Integer;
+    use crate::integers::prime::find_prime_with_bit_length_with_rng;
+    use crate::random::rand_state_from_seed;
+
+    use super::*;
+
+    fn product_of_factors(factors: &[(u64, u64)]) -> Integer {
+        let mut product = Integer::ONE.clone();
+        for (p, e) in factors {
+            if *p == MINUS_ONE {
+                if e % 2 == 1 {
+                    product = -product;
+                }
+            } else {
+                product *= Integer::from(*p).pow(*e as u32);
+            }
+        }
+        product
+    }
+
+    #[test]
+    fn test_sqrt_mod() {
+        for &p in small_primes().up_to(2000).iter().skip(1) {
+            let p = p as u64;
+            for a in 1..p.min(300) {
+                if pow_mod(a, (p - 1)/2, p) == 1 {
+                    let r = sqrt_mod(a, p);
+                    assert_eq!(r*r % p, a, "Wrong square root of {} modulo {}", a, p);
+                }
+            }
+            assert_eq!(inverse_mod(12345, p)*(12345 % p) % p, if 12345 % p == 0 { 0 } else { 1 });
+        }
+    }
+
+    #[test]
+    fn test_factor_base_and_polynomials() {
+        let n = Integer::from(1000000007u64)*Integer::from(998244353u64);
+        let base = factor_base(&n, 100);
+        assert_eq!(base.len(), 100);
+        assert_eq!(base[0].p, 2);
+        for prime in &base[1..] {
+            assert_eq!(prime.sqrt*prime.sqrt % prime.p, n.mod_u(prime.p as u32) as u64);
+        }
+
+        let largest = base.last().unwrap().p;
+        let polynomials = [Polynomial::first(&n), Polynomial::with_prime(&n, &polynomial_primes(&n, largest).next().unwrap())];
+        for polynomial in polynomials {
+            assert!((polynomial.b.square_ref().complete() - &n).is_divisible(&polynomial.a));
+            for x in [-1000i64, -3, 0, 7, 5000] {
+                let t = polynomial.square_root(x, &n);
+                let g = polynomial.value(x);
+                assert!((t.square() - &g).is_divisible(&n));
+            }
+            for prime in &base {
+                for root in polynomial.roots(prime) {
+                    assert!(polynomial.value(root as i64).is_divisible_u(prime.p as u32));
+                    assert!(polynomial.value(root as i64 - 3*prime.p as i64).is_divisible_u(prime.p as u32));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_relations_by_quadratic_sieve() {
+        let n = Integer::from(1000000007u64)*Integer::from(998244353u64);
+        let base = factor_base(&n, quadratic_sieve_factor_base_size(&n));
+        let relations = find_relations_by_quadratic_sieve(&n, &base, base.len() + 10);
+        assert!(relations.len() >= base.len() + 10);
+        for (t, factors) in &relations {
+            let difference = t.square_ref().complete() - product_of_factors(factors);
+            assert!(difference.is_divisible(&n), "{} does not give a relation", t);
+        }
+
+        for squares in find_all_squares_by_relations(&relations) {
+            let factor = factor_from_squares(&n, &relations, &squares);
+            assert!(n.is_divisible(&factor));
+        }
+    }
+
+    #[test]
+    fn test_find_factor_by_quadratic_sieve() {
+        let mut rng = rand_state_from_seed(&Integer::from(50));
+        for bits in [40, 60, 80, 100] {
+            let p = find_prime_with_bit_length_with_rng(bits/2, 0, &mut rng);
+            let q = find_prime_with_bit_length_with_rng(bits - bits/2, 0, &mut rng);
+            let n = (&p*&q).complete();
+            let factor = find_factor_by_quadratic_sieve(&n);
+            assert!(factor == p || factor == q, "Found {} for {} = {} * {}", factor, n, p, q);
+        }
+
+        let p = Integer::from(1000000007u64);
+        assert_eq!(find_factor_by_quadratic_sieve(&Integer::from(91)), 7);
+        assert_eq!(find_factor_by_quadratic_sieve(&p.square_ref().complete()), p);
+        assert_eq!(find_factor_by_quadratic_sieve(&p.clone().pow(3)), p);
+    }
+
+    // A semiprime of 60 digits with two factors of 30 digits. It takes tens of seconds in release
+    // builds and far longer in debug ones, so it only runs when asked for.
+    #[test]
+    #[ignore]
+    fn test_find_factor_by_quadratic_sieve_with_60_digits() {
+        let mut rng = rand_state_from_seed(&Integer::from(60));
+        let p = find_prime_with_bit_length_with_rng(99, 0, &mut rng);
+        let q = find_prime_with_bit_length_with_rng(99, 0, &mut rng);
+        let n = (&p*&q).complete();
+        assert_eq!(n.to_string().len(), 60);
+        let factor = find_factor_by_quadratic_sieve(&n);
+        assert!(factor == p || factor == q, "Found {} for {} = {} * {}", factor, n, p, q);
+    }
+
+    #[test]
+    fn test_factorization_by_quadratic_sieve() {
+        let p = Integer::from(1000000007u64);
+        let q = Integer::from(998244353u64);
+        let r = Integer::from(4294967291u64);
+        let n: Integer = Integer::from(12)*&p*&p*&q*&r;
+        let factors = factorization_by_quadratic_sieve(&n);
+        assert_eq!(factors, vec![Integer::from(2), Integer::from(2), Integer::from(3), q, p.clone(), p, r]);
+        assert_eq!(factorization_by_quadratic_sieve(&Integer::from(1)), Vec::<Integer>::new());
+        assert_eq!(factorization_by_quadratic_sieve(&Integer::from(97)), vec![Integer::from(97)]);
+    }
+}
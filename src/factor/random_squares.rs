@@ -1,9 +1,7 @@
-use gauss_jordan_elimination::gauss_elimination_generic;
 use itertools::Itertools;
 use rug::{integer::IsPrime, rand::RandState, Complete, Integer};
-use std::{collections::HashMap, fs, hash::Hash, io::{BufRead, BufReader}, ops::AddAssign, time::{SystemTime, UNIX_EPOCH}};
-use crate::algebraic_structure::z2::Z2;
-use num::traits::{Zero, One};
+use std::{collections::HashMap, hash::Hash, ops::AddAssign, time::{SystemTime, UNIX_EPOCH}};
+use crate::integers::prime::table::small_primes;
 
 
 // Returns factors of the square.
@@ -54,122 +52,101 @@ where
 }
 
 
-fn find_first_non_pivot(matrix: &Vec<Vec<Z2>>) -> Option<usize> {
-    for (i, row) in matrix.iter().enumerate() {
-        if !row[i].0 {
-            return Some(i);
-        }
-    }
-    if matrix.len() < matrix[0].len() {
-        Some(matrix.len())
-    } else {
-        None
-    }
-}
-
-
-fn find_all_pivots(matrix: &Vec<Vec<Z2>>) -> Vec<bool> {
-    let cols = matrix[0].len();
-    let rows = matrix.len();
-    let mut pivots: Vec<bool> = vec![false; cols];
-    // let mut count = 0;
-    for i in 0..rows {
-        for j in 0..cols {
-            if matrix[i][j].0 {
-                pivots[j] = true;
-                break
+// Every set of relations whose factorizations multiply to a square, as a basis of the null space
+// over Z/2Z of the parity matrix, which has a row for every relation and a column for every
+// prime that occurs with an odd exponent. The rows are packed into words and carry the relations
+// they are a sum of behind the parities, so that Gauss-Jordan elimination leaves exactly the
+// dependencies in the rows that never became a pivot.
+pub fn find_all_squares_by_relations(relations: &HashMap<Integer, Vec<(u64, u64)>>) -> Vec<Vec<&Integer>> {
+    let integers = relations.keys().collect_vec();
+    let primes: HashMap<u64, usize> = relations
+        .values()
+        .flatten()
+        .filter(|(_, exp)| exp % 2 == 1)
+        .map(|(p, _)| *p)
+        .unique()
+        .enumerate()
+        .map(|(i, p)| (p, i))
+        .collect();
+    let parity_words = primes.len().div_ceil(64);
+    let words = parity_words + integers.len().div_ceil(64);
+
+    let mut rows: Vec<Vec<u64>> = integers
+        .iter()
+        .enumerate()
+        .map(|(i, integer)| {
+            let mut row = vec![0u64; words];
+            for (p, exp) in relations.get(*integer).expect("Key should exist.") {
+                if exp % 2 == 1 {
+                    let column = primes[p];
+                    row[column/64] ^= 1 << (column % 64);
+                }
+            }
+            row[parity_words + i/64] |= 1 << (i % 64);
+            row
+        })
+        .collect();
+
+    let mut is_pivot = vec![false; rows.len()];
+    for column in 0..primes.len() {
+        let (word, bit) = (column/64, column % 64);
+        let Some(pivot) = (0..rows.len()).find(|r| !is_pivot[*r] && (rows[*r][word] >> bit) & 1 == 1) else {
+            continue;
+        };
+        is_pivot[pivot] = true;
+        // Earlier columns are already cleared in the pivot row.
+        let pivot_row = rows[pivot][word..].to_vec();
+        for (r, row) in rows.iter_mut().enumerate() {
+            if r != pivot && (row[word] >> bit) & 1 == 1 {
+                for (a, b) in row[word..].iter_mut().zip(&pivot_row) {
+                    *a ^= b;
+                }
             }
         }
     }
-    pivots
-}
 
+    rows.iter()
+        .zip(is_pivot)
+        .filter(|(_, pivot)| !pivot)
+        .map(|(row, _)| {
+            (0..integers.len())
+                .filter(|i| (row[parity_words + i/64] >> (i % 64)) & 1 == 1)
+                .map(|i| integers[i])
+                .collect()
+        })
+        .collect()
+}
 
-fn find_a_nonzero_solution(matrix: &Vec<Vec<Z2>>, size_of_solution: usize) -> Option<Vec<Z2>> {
-    let mut solution = vec![Z2::zero(); size_of_solution];
-    let pivots = find_all_pivots(matrix);
 
-    let first_non_pivot = pivots.iter().position(|x| !x).unwrap_or(matrix.len());
+pub fn find_squares_by_relations(relations: &HashMap<Integer, Vec<(u64, u64)>>) -> Option<Vec<&Integer>> {
+    find_all_squares_by_relations(relations).into_iter().next()
+}
 
-    if first_non_pivot >= size_of_solution {
-        return None;
-    }
 
-    solution[first_non_pivot] = Z2::one();
-    let mut ones: Vec<usize> = vec![first_non_pivot];
+// Given relations t^2 = prod p^e (mod n) whose products multiply to a square, x = prod t and
+// y = prod p^(e/2) over the merged exponents satisfy x^2 = y^2 (mod n), and gcd(n, y - x) is a
+// factor of n, which is trivial for about half of the choices of squares.
+pub fn factor_from_squares(n: &Integer, relations: &HashMap<Integer, Vec<(u64, u64)>>, squares: &[&Integer]) -> Integer {
+    let mut square1 = Integer::ONE.clone();
+    let mut square2_vec: Vec<(u64, u64)> = Vec::new();
+    for factor in squares {
+        square1 = square1 * *factor;
+        square1 = square1 % n;
 
-    for i in (0..matrix.len()).rev() {
-        let mut sum = false;
-        for j in &ones {
-            if matrix[i][*j].0 {
-                sum ^= true;
-            }
-        }
-        if sum {
-            solution[i] = Z2::one();
-            ones.push(i);
+        for prime_exp in relations.get(*factor).expect("Key exists.") {
+            square2_vec.push(*prime_exp);
         }
     }
 
-    Some(solution)
-
-    // for i in 0..*pivots.last().expect("Pivot vector is non-empty.") {
-    //     if i >= matrix.len() {
-    //         break;
-    //     }
-    //     let mut count = false;
-    //     for pivot in pivots {
-    //         if matrix[i][*pivot].0 {
-    //             count ^= true;
-    //         }
-    //     }
-    //     if count {
-    //         vector[i] = Z2::one();
-    //     }
-    // }
-    // for pivot in pivots {
-    //     vector[*pivot] = Z2::one();
-    // }
-    // Some(vector)
-}
-
-pub fn find_squares_by_relations(relations: &HashMap<Integer, Vec<(u64, u64)>>) -> Option<Vec<&Integer>> {
-    let integers = relations.keys().collect_vec();
-    let primes: Vec<u64> = relations.values().into_iter().flatten().filter(|(_, exp)| exp % 2 == 1 ).map(|(n, _)| *n as u64).unique().collect_vec();
-    let mut cols = integers.len();
-    let rows = primes.len();
+    square2_vec = merge_tuples(&square2_vec);
 
-    if primes.len() > integers.len() {
-        cols = primes.len();
-    }
-    
-    let mut choice_matrix: Vec<Vec<Z2>> = vec![vec![Z2::zero(); cols]; rows];
-    for (i, integer) in integers.iter().enumerate() {
-        for prime in relations.get(integer).expect("Key should exist.") {
-            if prime.1 % 2 == 1 {
-                if let Some(index) = primes.iter().position(|&p| p == prime.0) {
-                    choice_matrix[index][i] = Z2::one();
-                }
-            }
-        }
+    let mut square2 = Integer::ONE.clone();
+    for (prime, exp) in square2_vec {
+        square2 = square2 * Integer::from(prime).pow_mod(&Integer::from(exp/2), n).expect("Power exists.");
+        square2 = square2 % n;
     }
 
-    gauss_elimination_generic(&mut choice_matrix, gauss_jordan_elimination::GaussEliminationOption::PrepareReduce);
-
-    if let Some(choice_vector) = find_a_nonzero_solution(&choice_matrix, integers.len()) {
-        let mut relation_vector = Vec::new();
-        for (i, choice) in choice_vector.iter().enumerate() {
-            if i >= integers.len() {
-                break;
-            }
-            if choice.0 {
-                relation_vector.push(integers[i]);           
-            }
-        }
-        Some(relation_vector)
-    } else {
-        None
-    }
+    n.clone().gcd(&(&square2 - &square1).complete())
 }
 
 
@@ -191,36 +168,7 @@ pub fn find_factors_by_random_squares(n: &Integer, number_of_relations: usize) -
         assert_eq!(integer.clone().pow_mod(&Integer::from(2), &n).expect("Square exists."), product, "Failed checking {}, which then doesn't factor to {:#?}", integer, relations.get(integer).expect("Exists"))
     }
 
-    let mut square1 = Integer::ONE.clone();
-    let mut square2_vec: Vec<(u64, u64)> = Vec::new();
-    for factor in square_vec {
-        square1 = square1.clone() * factor;
-        square1 = square1.clone() % n;
-
-        for prime_exp in relations.get(factor).expect("Key exists.") {
-            square2_vec.push(*prime_exp);
-        }
-    }
-
-    square2_vec = merge_tuples(&square2_vec);
-
-    let mut square2 = Integer::ONE.clone();
-    for (prime, exp) in &square2_vec {
-        square2 = square2 * Integer::from(*prime).pow_mod(&Integer::from(*exp), &n).expect("Square exists.");
-        square2 = square2 % n;
-    }
-
-    square2_vec = square2_vec.iter().map(|(prime, exp)| (*prime, exp/2)).collect();
-
-    let mut square2 = Integer::ONE.clone();
-
-    for (prime, exp) in square2_vec {
-        square2 = square2 * prime.pow(exp as u32);
-        square2 = square2 % n;
-    }
-
-    // println!("Found: {}^2 = {}^2  (mod {})", &square1, &square2, &n);
-    n.clone().gcd(&(&square2 - &square1).complete())
+    factor_from_squares(n, &relations, &square_vec)
 }
 
 
@@ -236,19 +184,16 @@ pub fn find_two_real_factors_by_random_squares(n: &Integer, number_of_relations:
 }
 
 
-fn trial_division(t: &Integer) -> Option<Vec<(u64, u64)>> {
-    let chosen_primes_path = "chosen-primes";
-    if !fs::exists(chosen_primes_path).expect("Can't check existence of file.") {
-        panic!("There must exist a generated set of small-primes");
-    }
+// The factor base, i.e. the primes that used to be written to chosen-primes.
+const FACTOR_BASE_BOUND: usize = 100_000;
 
+
+fn trial_division(t: &Integer) -> Option<Vec<(u64, u64)>> {
     let mut t_clone = t.clone();
     let mut factors: Vec<(u64, u64)> = Vec::new();
 
-    let chosen_primes = fs::File::open(chosen_primes_path).expect("small-primes file should have been generated");
-    let reader = BufReader::new(chosen_primes);
-    for prime in reader.lines() {
-        let p = prime.expect("Line should exist").parse::<u64>().expect("Should be a positive integer");
+    for &p in small_primes().up_to(FACTOR_BASE_BOUND) {
+        let p = p as u64;
         while (&t_clone%p).complete() == 0 {
             if factors.len() == 0 {
                 factors.push((p, 1));
@@ -269,8 +214,7 @@ fn trial_division(t: &Integer) -> Option<Vec<(u64, u64)>> {
 }
 
 
-pub fn factorization_by_random_squares(n: &Integer, number_of_relations: usize, depth: usize) -> Vec<Integer> {
-    println!("Entered depth {} and we are now factorizing {}", depth, n);
+pub fn factorization_by_random_squares(n: &Integer, number_of_relations: usize) -> Vec<Integer> {
     if n.is_probably_prime(30) != IsPrime::No {
         return vec![n.clone()];
     }
@@ -284,8 +228,8 @@ pub fn factorization_by_random_squares(n: &Integer, number_of_relations: usize,
 
     let (factor1, factor2) = find_two_real_factors_by_random_squares(n, number_of_relations);
 
-    let mut factors1 = factorization_by_random_squares(&factor1, number_of_relations, depth+1);
-    let mut factors2 = factorization_by_random_squares(&factor2, number_of_relations, depth+1);
+    let mut factors1 = factorization_by_random_squares(&factor1, number_of_relations);
+    let mut factors2 = factorization_by_random_squares(&factor2, number_of_relations);
 
     factors1.append(&mut factors2);
     factors1
@@ -294,8 +238,7 @@ pub fn factorization_by_random_squares(n: &Integer, number_of_relations: usize,
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashSet, fs::File, hash::RandomState, os::unix::thread, process::{Command, Output}};
-    use crate::factor::file_handler::largest_chosen_prime;
+    use std::{collections::HashSet, hash::RandomState, os::unix::thread, process::{Command, Output}};
 
     use rand::{thread_rng, Rng};
     use rug::integer::IsPrime;
@@ -335,7 +278,7 @@ mod tests {
         let loops = 200;
         let mut rng = thread_rng();
 
-        let largest_prime = largest_chosen_prime();
+        let largest_prime = small_primes().up_to(FACTOR_BASE_BOUND).last().copied().expect("Factor base is not empty") as u64;
 
         for _ in 0..loops {
             let t = Integer::from(rng.gen_range(1..u64::MAX));
@@ -422,14 +365,8 @@ mod tests {
 
     fn get_random_prime(max: usize) -> u64 {
         let mut rng = thread_rng();
-        let filepath = "small-primes";
-        let file = File::open(filepath).expect("File should exist.");
-        let reader = BufReader::new(file);
-        let lines: Vec<u64> = reader.lines().collect_vec().iter().map(|x| x.as_ref().expect("Every line should exist.").parse::<u64>().expect("Every line should be an integer.")).collect_vec();
-        // let number_of_lines = lines.len();
-        let line_number = rng.gen_range(1..max);
-        let prime: u64 = lines[line_number];
-        prime
+        let index = rng.gen_range(1..max);
+        small_primes().get_primes()[index] as u64
     }
 
 
@@ -446,7 +383,7 @@ mod tests {
             for _ in 0..number_of_primes {
                 integer *= get_random_prime(max) as u128;
             }
-            let factors = factorization_by_random_squares(&Integer::from(integer), number_of_relations, 0);
+            let factors = factorization_by_random_squares(&Integer::from(integer), number_of_relations);
 
             let mut prod = Integer::ONE.clone();
             for factor in factors {
@@ -1,2 +1,7 @@
+pub mod arith;
+pub mod continued_fraction;
 pub mod integer_computations;
 pub mod prime;
+pub mod prime_count;
+pub mod residue_number_system;
+pub mod sieve;
@@ -0,0 +1,233 @@
+use itertools::Itertools;
+use rug::{ops::Pow, Complete, Integer};
+use crate::factor::quadratic_sieve::factorization_by_quadratic_sieve;
+use crate::integers::integer_computations::pow_rug;
+
+
+// Factors |n| into pairs (p, e) of distinct primes and exponents, sorted by p. Trial division
+// removes the small primes and the quadratic sieve splits what remains, so that n with two large
+// prime factors are factored as well.
+pub fn factor_with_multiplicities(n: &Integer) -> Vec<(Integer, u32)> {
+    let n = n.clone().abs();
+    if n <= 1 {
+        return Vec::new();
+    }
+    factorization_by_quadratic_sieve(&n)
+        .into_iter()
+        .dedup_with_count()
+        .map(|(count, p)| (p, count as u32))
+        .collect()
+}
+
+
+// Computes (a/n) for n odd and positive, using quadratic reciprocity:
+//      (2/n) = -1 iff n = 3, 5 (mod 8)    and    (a/n)(n/a) = -1 iff a = n = 3 (mod 4)
+pub fn jacobi(a: &Integer, n: &Integer) -> i32 {
+    assert!(*n > 0 && n.is_odd(), "The Jacobi symbol is only defined for odd positive n");
+    let mut a: Integer = a.modulo_ref(n).complete();
+    let mut n: Integer = n.clone();
+    let mut t = 1;
+
+    while !a.is_zero() {
+        while a.is_even() {
+            a >>= 1;
+            let r = n.mod_u(8);
+            if r == 3 || r == 5 {
+                t = -t;
+            }
+        }
+        std::mem::swap(&mut a, &mut n);
+        if a.mod_u(4) == 3 && n.mod_u(4) == 3 {
+            t = -t;
+        }
+        a = a.modulo(&n);
+    }
+
+    if n == 1 {
+        t
+    } else {
+        0
+    }
+}
+
+
+// Extends the Jacobi symbol to all n, with (a/-1) = sign(a) and (a/2) = (2/a) for odd a.
+pub fn kronecker(a: &Integer, n: &Integer) -> i32 {
+    if n.is_zero() {
+        return if a.clone().abs() == 1 { 1 } else { 0 };
+    }
+
+    let mut t = 1;
+    let mut n: Integer = n.clone();
+    if n.is_negative() {
+        n = -n;
+        if a.is_negative() {
+            t = -t;
+        }
+    }
+
+    let twos = n.find_one(0).expect("n is non-zero");
+    if twos > 0 {
+        if a.is_even() {
+            return 0;
+        }
+        let r = a.mod_u(8);
+        if twos % 2 == 1 && (r == 3 || r == 5) {
+            t = -t;
+        }
+        n >>= twos;
+    }
+
+    t * jacobi(a, &n)
+}
+
+
+pub fn euler_totient(n: &Integer) -> Integer {
+    assert!(*n > 0, "Euler's totient is only defined for positive n");
+    let mut phi: Integer = Integer::ONE.clone();
+    for (p, e) in factor_with_multiplicities(n) {
+        phi *= (&p - Integer::ONE).complete() * p.clone().pow(e - 1);
+    }
+    phi
+}
+
+
+// The exponent of (Z/nZ)*, which is lcm of lambda(p^e) over the prime powers of n, where
+//      lambda(2^e) = 2^(e-2) for e >= 3    and    lambda(p^e) = phi(p^e) otherwise.
+pub fn carmichael_lambda(n: &Integer) -> Integer {
+    assert!(*n > 0, "The Carmichael function is only defined for positive n");
+    let mut lambda: Integer = Integer::ONE.clone();
+    for (p, e) in factor_with_multiplicities(n) {
+        let lambda_p: Integer = if p == 2 && e >= 3 {
+            Integer::from(1) << (e - 2)
+        } else {
+            (&p - Integer::ONE).complete() * p.clone().pow(e - 1)
+        };
+        lambda.lcm_mut(&lambda_p);
+    }
+    lambda
+}
+
+
+pub fn mobius(n: &Integer) -> i32 {
+    assert!(*n > 0, "The Möbius function is only defined for positive n");
+    let factors = factor_with_multiplicities(n);
+    if factors.iter().any(|(_, e)| *e > 1) {
+        0
+    } else if factors.len() % 2 == 0 {
+        1
+    } else {
+        -1
+    }
+}
+
+
+pub fn divisor_count(n: &Integer) -> Integer {
+    assert!(*n > 0, "The divisor count is only defined for positive n");
+    let mut count: Integer = Integer::ONE.clone();
+    for (_, e) in factor_with_multiplicities(n) {
+        count *= e + 1;
+    }
+    count
+}
+
+
+// sigma(n) = prod (p^(e+1) - 1)/(p - 1) over the prime powers of n.
+pub fn divisor_sum(n: &Integer) -> Integer {
+    assert!(*n > 0, "The divisor sum is only defined for positive n");
+    let mut sum: Integer = Integer::ONE.clone();
+    for (p, e) in factor_with_multiplicities(n) {
+        sum *= (p.clone().pow(e + 1) - Integer::ONE) / (p - Integer::ONE);
+    }
+    sum
+}
+
+
+// All positive divisors of n in increasing order.
+pub fn divisors(n: &Integer) -> Vec<Integer> {
+    assert!(*n > 0, "Divisors are only listed for positive n");
+    let mut divisors: Vec<Integer> = vec![Integer::ONE.clone()];
+    for (p, e) in factor_with_multiplicities(n) {
+        let mut extended: Vec<Integer> = Vec::with_capacity(divisors.len() * (e as usize + 1));
+        for d in &divisors {
+            let mut power: Integer = d.clone();
+            extended.push(power.clone());
+            for _ in 0..e {
+                power *= &p;
+                extended.push(power.clone());
+            }
+        }
+        divisors = extended;
+    }
+    divisors.sort();
+    divisors
+}
+
+
+// Smallest k > 0 with a^k = 1 (mod n), found by removing prime factors from lambda(n) as long
+// as the power stays 1. Returns None if a is not a unit mod n.
+pub fn multiplicative_order(a: &Integer, n: &Integer) -> Option<Integer> {
+    let n: Integer = n.clone().abs();
+    if n.is_zero() || a.gcd_ref(&n).complete() != 1 {
+        return None;
+    }
+    let a: Integer = a.modulo_ref(&n).complete();
+
+    let mut order = carmichael_lambda(&n);
+    for (q, _) in factor_with_multiplicities(&order) {
+        while order.is_divisible(&q) {
+            let candidate: Integer = (&order / &q).complete();
+            if pow_rug(&a, &candidate, &n) != Integer::ONE.clone() % &n {
+                break;
+            }
+            order = candidate;
+        }
+    }
+    Some(order)
+}
+
+
+// (Z/nZ)* is cyclic exactly for n = 1, 2, 4, p^k and 2p^k with p an odd prime.
+pub fn has_primitive_root(n: &Integer) -> bool {
+    let n: Integer = n.clone().abs();
+    if n.is_zero() {
+        return false;
+    }
+    if n <= 4 {
+        return true;
+    }
+    let odd: Integer = if n.mod_u(4) == 2 { (&n >> 1u32).complete() } else { n };
+    let factors = factor_with_multiplicities(&odd);
+    factors.len() == 1 && factors[0].0 != 2
+}
+
+
+// Smallest positive generator of (Z/nZ)*, if one exists.
+pub fn primitive_root(n: &Integer) -> Option<Integer> {
+    if !has_primitive_root(n) {
+        return None;
+    }
+    let n: Integer = n.clone().abs();
+    if n <= 2 {
+        return Some(Integer::ONE.clone());
+    }
+
+    let phi = euler_totient(&n);
+    let primes: Vec<Integer> = factor_with_multiplicities(&phi).into_iter().map(|(q, _)| q).collect();
+    Some(primitive_root_from_factors(&n, &phi, &primes))
+}
+
+
+// Smallest g > 1 of order phi modulo n, given the distinct primes dividing phi = phi(n), which
+// avoids factoring when they are already known, e.g. for a prime generated with p - 1 factored.
+// Does not return if (Z/nZ)* is not cyclic.
+pub fn primitive_root_from_factors(n: &Integer, phi: &Integer, primes: &[Integer]) -> Integer {
+    let mut g: Integer = Integer::from(2);
+    loop {
+        if g.gcd_ref(n).complete() == 1
+            && primes.iter().all(|q| pow_rug(&g, &(phi / q).complete(), n) != 1) {
+            return g;
+        }
+        g += 1;
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use rug::{ops::DivRounding, Complete, Integer};
+
+
+// Expansion [a_0; a_1, ..., a_(k-1), (a_k, ..., a_(k+r-1))] of a quadratic irrational, where the
+// bracketed part repeats forever. A rational number has an empty period.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuadraticContinuedFraction {
+    pub pre_period: Vec<Integer>,
+    pub period: Vec<Integer>,
+}
+
+
+impl QuadraticContinuedFraction {
+    pub fn quotient(&self, i: usize) -> Option<&Integer> {
+        if i < self.pre_period.len() {
+            return self.pre_period.get(i);
+        }
+        if self.period.is_empty() {
+            return None;
+        }
+        self.period.get((i - self.pre_period.len()) % self.period.len())
+    }
+
+
+    pub fn period_len(&self) -> usize {
+        self.period.len()
+    }
+
+
+    // The first count convergents, or all of them if the expansion is finite.
+    pub fn convergents(&self, count: usize) -> Vec<(Integer, Integer)> {
+        let quotients: Vec<Integer> = (0..count).map_while(|i| self.quotient(i).cloned()).collect();
+        convergents(&quotients)
+    }
+}
+
+
+// Expands p/q as [a_0; a_1, ..., a_k] by the same divisions as the euclidean algorithm.
+pub fn continued_fraction_rational(p: &Integer, q: &Integer) -> Vec<Integer> {
+    assert!(!q.is_zero(), "Denominator must be non-zero");
+    let mut a: Integer = p.clone();
+    let mut b: Integer = q.clone();
+    let mut quotients: Vec<Integer> = Vec::new();
+
+    while !b.is_zero() {
+        let (q, r) = a.div_rem_floor_ref(&b).complete();
+        quotients.push(q);
+        a = b;
+        b = r;
+    }
+    quotients
+}
+
+
+// Convergents p_i/q_i from the recursion
+//      p_i = a_i*p_(i-1) + p_(i-2),    q_i = a_i*q_(i-1) + q_(i-2)
+// with p_(-1) = 1, p_(-2) = 0, q_(-1) = 0 and q_(-2) = 1.
+pub fn convergents(quotients: &[Integer]) -> Vec<(Integer, Integer)> {
+    let mut convergents: Vec<(Integer, Integer)> = Vec::with_capacity(quotients.len());
+    let (mut p1, mut p2) = (Integer::ONE.clone(), Integer::ZERO.clone());
+    let (mut q1, mut q2) = (Integer::ZERO.clone(), Integer::ONE.clone());
+
+    for a in quotients {
+        let p = (a * &p1).complete() + &p2;
+        let q = (a * &q1).complete() + &q2;
+        p2 = p1;
+        q2 = q1;
+        p1 = p.clone();
+        q1 = q.clone();
+        convergents.push((p, q));
+    }
+    convergents
+}
+
+
+// Expands (p + sqrt(d))/q for d >= 0. Each complete quotient is kept on the form (P + sqrt(d))/Q
+// with Q | d - P^2, so the period is found as soon as a pair (P, Q) repeats.
+pub fn continued_fraction_quadratic(p: &Integer, q: &Integer, d: &Integer) -> QuadraticContinuedFraction {
+    assert!(!q.is_zero(), "Denominator must be non-zero");
+    assert!(!d.is_negative(), "Radicand must be non-negative");
+
+    let (s, rem) = d.sqrt_rem_ref().complete();
+    if rem.is_zero() {
+        return QuadraticContinuedFraction {
+            pre_period: continued_fraction_rational(&(p + &s).complete(), q),
+            period: Vec::new(),
+        };
+    }
+
+    // Scale by |q| to make sure Q | d - P^2.
+    let (mut big_p, mut big_q, d, s): (Integer, Integer, Integer, Integer) = if (d - p.square_ref().complete()).is_divisible(q) {
+        (p.clone(), q.clone(), d.clone(), s)
+    } else {
+        let scale: Integer = q.clone().abs();
+        let d: Integer = d * scale.square_ref().complete();
+        let s: Integer = d.sqrt_ref().complete();
+        ((p * &scale).complete(), q * scale, d, s)
+    };
+
+    let mut quotients: Vec<Integer> = Vec::new();
+    let mut seen: HashMap<(Integer, Integer), usize> = HashMap::new();
+
+    loop {
+        if let Some(start) = seen.get(&(big_p.clone(), big_q.clone())) {
+            let period = quotients.split_off(*start);
+            return QuadraticContinuedFraction {
+                pre_period: quotients,
+                period,
+            };
+        }
+        seen.insert((big_p.clone(), big_q.clone()), quotients.len());
+
+        // floor((P + sqrt(d))/Q), using that sqrt(d) lies strictly between s and s + 1.
+        let a: Integer = if big_q.is_positive() {
+            (&big_p + &s).complete().div_floor(big_q.clone())
+        } else {
+            (-(&big_p + &s).complete() - 1u32).div_floor(-big_q.clone())
+        };
+
+        big_p = (&a * &big_q).complete() - big_p;
+        big_q = (&d - big_p.square_ref().complete()) / big_q;
+        quotients.push(a);
+    }
+}
+
+
+pub fn continued_fraction_sqrt(d: &Integer) -> QuadraticContinuedFraction {
+    continued_fraction_quadratic(&Integer::ZERO, Integer::ONE, d)
+}
+
+
+fn is_pell_radicand(d: &Integer) -> bool {
+    d.is_positive() && !d.is_perfect_square()
+}
+
+
+// With r the period of sqrt(d), (p_(r-1), q_(r-1)) solves x^2 - d*y^2 = (-1)^r, and the
+// solution of the positive equation for odd r is found one period later.
+fn pell_from_period(d: &Integer, negative: bool) -> Option<(Integer, Integer)> {
+    if !is_pell_radicand(d) {
+        return None;
+    }
+    let fraction = continued_fraction_sqrt(d);
+    let r = fraction.period_len();
+    let odd = r % 2 == 1;
+
+    let index = match (negative, odd) {
+        (true, false) => return None,
+        (true, true) | (false, false) => r,
+        (false, true) => 2*r,
+    };
+    fraction.convergents(index).pop()
+}
+
+
+// Smallest positive solution of x^2 - d*y^2 = 1, or None if d is not a positive non-square.
+pub fn pell_fundamental(d: &Integer) -> Option<(Integer, Integer)> {
+    pell_from_period(d, false)
+}
+
+
+// Smallest positive solution of x^2 - d*y^2 = -1, which exists exactly when the period of
+// sqrt(d) is odd.
+pub fn negative_pell_fundamental(d: &Integer) -> Option<(Integer, Integer)> {
+    pell_from_period(d, true)
+}
+
+
+// (x + y*sqrt(d))(u + v*sqrt(d)) = (xu + dyv) + (xv + yu)sqrt(d)
+fn compose(d: &Integer, (x, y): &(Integer, Integer), (u, v): &(Integer, Integer)) -> (Integer, Integer) {
+    (
+        (x*u).complete() + (d*y).complete()*v,
+        (x*v).complete() + (y*u).complete(),
+    )
+}
+
+
+// The first count positive solutions of x^2 - d*y^2 = 1, as powers of the fundamental one.
+pub fn pell_solutions(d: &Integer, count: usize) -> Vec<(Integer, Integer)> {
+    let Some(fundamental) = pell_fundamental(d) else {
+        return Vec::new();
+    };
+    let mut solutions: Vec<(Integer, Integer)> = Vec::with_capacity(count);
+    let mut solution = fundamental.clone();
+    for _ in 0..count {
+        solutions.push(solution.clone());
+        solution = compose(d, &solution, &fundamental);
+    }
+    solutions
+}
+
+
+// Two solutions of x^2 - d*y^2 = n are in the same class if they differ by a unit of norm 1,
+// which is the case exactly when
+//      x*u - d*y*v = 0 (mod n)    and    y*u - x*v = 0 (mod n).
+fn same_class(d: &Integer, n: &Integer, (x, y): &(Integer, Integer), (u, v): &(Integer, Integer)) -> bool {
+    let first: Integer = (x*u).complete() - (d*y).complete()*v;
+    let second: Integer = (y*u).complete() - (x*v).complete();
+    first.is_divisible(n) && second.is_divisible(n)
+}
+
+
+// Fundamental solutions of x^2 - d*y^2 = n, one for each class, by Nagell's bounds. With
+// (x_1, y_1) the fundamental solution of the Pell equation, the fundamental solution of every
+// class satisfies
+//      0 <= y <= y_1 sqrt(n / (2(x_1 + 1)))                       for n > 0,
+//      sqrt(-n/d) <= y <= y_1 sqrt(-n / (2(x_1 - 1)))             for n < 0,
+// so the search is linear in y_1 sqrt(|n|) and only meant for moderate n.
+pub fn generalized_pell_fundamental(d: &Integer, n: &Integer) -> Vec<(Integer, Integer)> {
+    let Some((x1, y1)) = pell_fundamental(d) else {
+        return Vec::new();
+    };
+    if n.is_zero() {
+        return vec![(Integer::ZERO.clone(), Integer::ZERO.clone())];
+    }
+
+    let abs_n: Integer = n.clone().abs();
+    let (denominator, mut y): (Integer, Integer) = if n.is_positive() {
+        (Integer::from(2) * (&x1 + Integer::ONE).complete(), Integer::ZERO.clone())
+    } else {
+        let lower: Integer = (&abs_n / d).complete().sqrt();
+        (Integer::from(2) * (&x1 - Integer::ONE).complete(), lower)
+    };
+    let bound: Integer = y1.square_ref().complete() * &abs_n;
+
+    let mut solutions: Vec<(Integer, Integer)> = Vec::new();
+    while y.square_ref().complete() * &denominator <= bound {
+        let x_squared: Integer = n + (d * y.square_ref().complete());
+        if !x_squared.is_negative() && x_squared.is_perfect_square() {
+            let x: Integer = x_squared.sqrt();
+            for candidate in [(x.clone(), y.clone()), (-x, y.clone())] {
+                if !solutions.iter().any(|s| same_class(d, n, s, &candidate)) {
+                    solutions.push(candidate);
+                }
+            }
+        }
+        y += 1;
+    }
+    solutions
+}
+
+
+// The first count solutions in each class of x^2 - d*y^2 = n, starting from the fundamental
+// solution of the class.
+pub fn generalized_pell_solutions(d: &Integer, n: &Integer, count: usize) -> Vec<Vec<(Integer, Integer)>> {
+    let Some(unit) = pell_fundamental(d) else {
+        return Vec::new();
+    };
+    generalized_pell_fundamental(d, n)
+        .into_iter()
+        .map(|fundamental| {
+            let mut class: Vec<(Integer, Integer)> = Vec::with_capacity(count);
+            let mut solution = fundamental;
+            for _ in 0..count {
+                let next = compose(d, &solution, &unit);
+                class.push(solution);
+                solution = next;
+            }
+            class
+        })
+        .collect()
+}
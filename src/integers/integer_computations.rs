@@ -35,15 +35,159 @@ pub fn extended_euclidean_ordered(a: &Integer, b: &Integer) -> (Integer, Integer
     (a1, x2, y2)
 }
 
+// Operands below this many bits go through the binary variant, where shifts and subtractions are
+// cheap compared to the bookkeeping Lehmer needs.
+const LEHMER_THRESHOLD_BITS: u32 = 256;
 
-pub fn extended_euclidean_to_integers<T: HasRepresentation + Clone>(a: &Element<T>, b: &Element<T>) -> (Integer, Integer, Integer) {
-    let a_rep: &Integer = a.get_rep();
-    let b_rep: &Integer = b.get_rep();
-    if a_rep > b_rep {
-        extended_euclidean_ordered(a_rep, b_rep)
+
+// Returns (d, x, y) with d = gcd(a, b) >= 0 and a*x + b*y = d, for any sign and order of a and b.
+pub fn extended_gcd(a: &Integer, b: &Integer) -> (Integer, Integer, Integer) {
+    if a.significant_bits().max(b.significant_bits()) < LEHMER_THRESHOLD_BITS {
+        binary_extended_gcd(a, b)
     } else {
-        extended_euclidean_ordered(b_rep, a_rep)
+        lehmer_extended_gcd(a, b)
+    }
+}
+
+
+// Moves the signs of a and b over to the coefficients of a solution found for |a| and |b|.
+fn apply_signs(a: &Integer, b: &Integer, (d, x, y): (Integer, Integer, Integer)) -> (Integer, Integer, Integer) {
+    let x = if a.is_negative() { -x } else { x };
+    let y = if b.is_negative() { -y } else { y };
+    (d, x, y)
+}
+
+
+// Binary extended gcd as in Handbook of Applied Cryptography, algorithm 14.61, which only uses
+// shifts, additions and subtractions.
+pub fn binary_extended_gcd(a: &Integer, b: &Integer) -> (Integer, Integer, Integer) {
+    if a.is_zero() {
+        return apply_signs(a, b, (b.clone().abs(), Integer::ZERO.clone(), Integer::ONE.clone()));
+    }
+    if b.is_zero() {
+        return apply_signs(a, b, (a.clone().abs(), Integer::ONE.clone(), Integer::ZERO.clone()));
     }
+
+    let mut x: Integer = a.clone().abs();
+    let mut y: Integer = b.clone().abs();
+    let shift = x.find_one(0).unwrap().min(y.find_one(0).unwrap());
+    x >>= shift;
+    y >>= shift;
+
+    // Invariants: u = A*x + B*y and v = C*x + D*y.
+    let mut u: Integer = x.clone();
+    let mut v: Integer = y.clone();
+    let mut big_a: Integer = Integer::ONE.clone();
+    let mut big_b: Integer = Integer::ZERO.clone();
+    let mut big_c: Integer = Integer::ZERO.clone();
+    let mut big_d: Integer = Integer::ONE.clone();
+
+    while !u.is_zero() {
+        while u.is_even() {
+            u >>= 1;
+            if big_a.is_even() && big_b.is_even() {
+                big_a >>= 1;
+                big_b >>= 1;
+            } else {
+                big_a = (big_a + &y) >> 1;
+                big_b = (big_b - &x) >> 1;
+            }
+        }
+        while v.is_even() {
+            v >>= 1;
+            if big_c.is_even() && big_d.is_even() {
+                big_c >>= 1;
+                big_d >>= 1;
+            } else {
+                big_c = (big_c + &y) >> 1;
+                big_d = (big_d - &x) >> 1;
+            }
+        }
+        if u >= v {
+            u -= &v;
+            big_a -= &big_c;
+            big_b -= &big_d;
+        } else {
+            v -= &u;
+            big_c -= &big_a;
+            big_d -= &big_b;
+        }
+    }
+
+    apply_signs(a, b, (v << shift, big_c, big_d))
+}
+
+
+// Lehmer's extended gcd (Knuth, TAOCP vol. 2, algorithm L). Quotients are simulated on the
+// leading 62 bits of the operands and collected into a 2x2 matrix, so that most full-precision
+// divisions are replaced by a few multiplications.
+pub fn lehmer_extended_gcd(a: &Integer, b: &Integer) -> (Integer, Integer, Integer) {
+    let swapped = a.cmp_abs(b) == std::cmp::Ordering::Less;
+    let (mut u, mut v): (Integer, Integer) = if swapped {
+        (b.clone().abs(), a.clone().abs())
+    } else {
+        (a.clone().abs(), b.clone().abs())
+    };
+
+    // Invariants: u = xu*|a| + yu*|b| and v = xv*|a| + yv*|b|, up to the swap.
+    let mut xu: Integer = Integer::ONE.clone();
+    let mut yu: Integer = Integer::ZERO.clone();
+    let mut xv: Integer = Integer::ZERO.clone();
+    let mut yv: Integer = Integer::ONE.clone();
+
+    while v.significant_bits() > 62 {
+        let shift = u.significant_bits() - 62;
+        let mut u_hat: i128 = (&u >> shift).complete().to_i128().expect("Leading bits fit in 62 bits.");
+        let mut v_hat: i128 = (&v >> shift).complete().to_i128().expect("Leading bits fit in 62 bits.");
+        let (mut big_a, mut big_b, mut big_c, mut big_d): (i128, i128, i128, i128) = (1, 0, 0, 1);
+
+        while v_hat + big_c != 0 && v_hat + big_d != 0 {
+            let q = (u_hat + big_a) / (v_hat + big_c);
+            if q != (u_hat + big_b) / (v_hat + big_d) {
+                break;
+            }
+            (big_a, big_c) = (big_c, big_a - q*big_c);
+            (big_b, big_d) = (big_d, big_b - q*big_d);
+            (u_hat, v_hat) = (v_hat, u_hat - q*v_hat);
+        }
+
+        if big_b == 0 {
+            let (q, r) = u.div_rem_floor_ref(&v).complete();
+            let x = xu - &q*&xv;
+            let y = yu - &q*&yv;
+            (u, v) = (v, r);
+            (xu, xv) = (xv, x);
+            (yu, yv) = (yv, y);
+        } else {
+            let combine = |s: &Integer, t: &Integer, p: i128, q: i128| -> Integer {
+                s*Integer::from(p) + t*Integer::from(q)
+            };
+            (u, v) = (combine(&u, &v, big_a, big_b), combine(&u, &v, big_c, big_d));
+            (xu, xv) = (combine(&xu, &xv, big_a, big_b), combine(&xu, &xv, big_c, big_d));
+            (yu, yv) = (combine(&yu, &yv, big_a, big_b), combine(&yu, &yv, big_c, big_d));
+        }
+    }
+
+    while !v.is_zero() {
+        let (q, r) = u.div_rem_floor_ref(&v).complete();
+        let x = xu - &q*&xv;
+        let y = yu - &q*&yv;
+        (u, v) = (v, r);
+        (xu, xv) = (xv, x);
+        (yu, yv) = (yv, y);
+    }
+
+    if swapped {
+        apply_signs(a, b, (u, yu, xu))
+    } else {
+        apply_signs(a, b, (u, xu, yu))
+    }
+}
+
+
+
+pub fn extended_euclidean_to_integers<T: HasRepresentation + Clone>(a: &Element<T>, b: &Element<T>) -> (Integer, Integer, Integer) {
+    extended_gcd(a.get_rep(), b.get_rep())
 }
 
 
@@ -72,3 +216,121 @@ pub fn naive_pow(a: &Integer, b: &Integer, n: &Integer) -> Integer {
     }
     product
 }
+
+
+// Precomputes mu = floor(4^k / n) for a fixed modulus n of k bits, such that any 0 <= x < n^2 can
+// be reduced with two multiplications and shifts instead of a division:
+//      q = ((x >> (k-1)) * mu) >> (k+1),    x - q*n < 3n
+#[derive(Debug, Clone)]
+pub struct BarrettContext {
+    modulus: Integer,
+    mu: Integer,
+    k: u32,
+}
+
+
+impl BarrettContext {
+    pub fn new(modulus: Integer) -> Option<BarrettContext> {
+        if modulus <= 0 {
+            return None
+        }
+        let k = modulus.significant_bits();
+        let mu = (Integer::ONE << (2*k)).complete() / &modulus;
+        Some(BarrettContext {
+            modulus,
+            mu,
+            k,
+        })
+    }
+
+
+    pub fn get_modulus(&self) -> &Integer {
+        &self.modulus
+    }
+
+
+    pub fn reduce(&self, x: &Integer) -> Integer {
+        // The estimate of q only holds for 0 <= x < 4^k, anything else is reduced the usual way.
+        if x.is_negative() || x.significant_bits() > 2*self.k {
+            return x.modulo_ref(&self.modulus).complete();
+        }
+        let q: Integer = ((x >> (self.k - 1)).complete() * &self.mu) >> (self.k + 1);
+        let mut r: Integer = x - q*&self.modulus;
+        while r >= self.modulus {
+            r -= &self.modulus;
+        }
+        r
+    }
+
+
+    pub fn mul(&self, a: &Integer, b: &Integer) -> Integer {
+        self.reduce(&(a * b).complete())
+    }
+
+
+    pub fn square(&self, a: &Integer) -> Integer {
+        self.reduce(&a.square_ref().complete())
+    }
+
+
+    // Same square-and-multiply as pow_rug, but every reduction goes through the context.
+    pub fn pow(&self, a: &Integer, b: &Integer) -> Integer {
+        let mut product: Integer = self.reduce(Integer::ONE);
+        let mut base = self.reduce(a);
+        let mut exponent = b.clone();
+
+        while exponent != 0 {
+            if exponent.get_bit(0) {
+                product = self.mul(&product, &base);
+            }
+            base = self.square(&base);
+            exponent >>= 1;
+        }
+        product
+    }
+}
+
+
+// As 2^n = 1 (mod 2^n - 1), the bits above position n can be folded back onto the lower ones:
+//      x = hi * 2^n + lo = hi + lo  (mod 2^n - 1)
+pub fn reduce_mod_mersenne(x: &Integer, n: u32) -> Integer {
+    let modulus: Integer = (Integer::ONE << n).complete() - 1u32;
+    if x.is_negative() {
+        return x.modulo_ref(&modulus).complete();
+    }
+    let mut r: Integer = x.clone();
+    while r.significant_bits() > n {
+        let hi: Integer = (&r >> n).complete();
+        r.keep_bits_mut(n);
+        r += hi;
+    }
+    if r == modulus {
+        r = Integer::ZERO.clone();
+    }
+    r
+}
+
+
+// As 2^n = -1 (mod 2^n + 1), the bits above position n are subtracted from the lower ones:
+//      x = hi * 2^n + lo = lo - hi  (mod 2^n + 1)
+pub fn reduce_mod_fermat(x: &Integer, n: u32) -> Integer {
+    let modulus: Integer = (Integer::ONE << n).complete() + 1u32;
+    if x.is_negative() {
+        return x.modulo_ref(&modulus).complete();
+    }
+    let mut r: Integer = x.clone();
+    // The difference may turn negative, but its absolute value keeps shrinking until it fits
+    // in n + 1 bits, after which at most two corrections are needed.
+    while r.significant_bits() > n + 1 {
+        let hi: Integer = (&r >> n).complete();
+        r.keep_bits_mut(n);
+        r -= hi;
+    }
+    while r.is_negative() {
+        r += &modulus;
+    }
+    while r >= modulus {
+        r -= &modulus;
+    }
+    r
+}
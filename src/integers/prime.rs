@@ -1,37 +1,50 @@
-use rand::{thread_rng, Rng};
+use itertools::Itertools;
 use rug::rand::RandState;
-use rug::{Complete, Integer};
-use std::fs;
-use std::io::{BufReader, BufRead};
-use std::str::FromStr;
-use crate::random::{randint_bits_odd, randint_bits};
-use crate::integers::integer_computations::pow_rug;
+use rug::{ops::DivRounding, Complete, Integer};
+use crate::random::{rand_state_from_entropy, random_below, random_bits_exact, random_odd_bits};
+use crate::integers::arith::jacobi;
+use crate::integers::integer_computations::{pow_rug, reduce_mod_fermat, reduce_mod_mersenne, BarrettContext};
+use crate::integers::sieve::next_prime;
+
+pub mod aks;
+pub mod certificate;
+pub mod generator;
+pub mod parallel;
+pub mod provable;
+pub mod pseudoprime;
+pub mod rounds;
+pub mod table;
+
+use table::{small_primes, PrimeTable};
+
+
+pub fn is_likely_prime_with_trial_division(candidate: &Integer, t: usize, bound: usize) -> bool {
+    is_likely_prime_with_table(candidate, t, bound, small_primes())
+}
 
 
-pub fn is_likely_prime_with_trial_division(candidate: &Integer, n: usize, bound: usize) -> bool {
-    if bound == 0 {
-        return rabin_miller_is_prime(candidate, n);
-    }
-    let small_primes = fs::File::open("small-primes").expect("small-primes file should have been generated");
-    let reader = BufReader::new(small_primes);
-    for prime in reader.lines() {
-        let p: Integer = Integer::from_str(&prime.unwrap()).expect("All entries of small-primes should be integers.");
-        if p > bound {
-            break;
-        }
-        if candidate > &p && candidate%p == 0 {
-            return false
-        }         
-    }
+// Trial division by the primes of the table up to bound before Baillie-PSW and t extra
+// Rabin-Miller rounds.
+pub fn is_likely_prime_with_table(candidate: &Integer, t: usize, bound: usize, table: &PrimeTable) -> bool {
+    !has_factor_in_table(candidate, bound, table) && passes_primality_checks(candidate, t)
+}
+
 
-    rabin_miller_is_prime(candidate, n)
+// Whether a prime of the table up to bound properly divides the candidate.
+fn has_factor_in_table(candidate: &Integer, bound: usize, table: &PrimeTable) -> bool {
+    table.up_to(bound).iter().any(|&p| *candidate > p && candidate.is_divisible_u(p))
 }
 
 
 pub fn fermat_is_prime(n: &Integer, reps: usize) -> bool {
-    let mut rng = RandState::new();
+    fermat_is_prime_with_rng(n, reps, &mut rand_state_from_entropy())
+}
+
+
+// Takes the random state for the bases from the caller, e.g. a seeded one for reproducible runs.
+pub fn fermat_is_prime_with_rng(n: &Integer, reps: usize, rng: &mut RandState) -> bool {
     for _ in 0..reps {
-        let a = Integer::ONE + (n-Integer::ONE.clone()).random_below_ref(&mut rng).complete();
+        let a = Integer::ONE + (n-Integer::ONE.clone()).random_below_ref(rng).complete();
         if pow_rug(&a, &(n-Integer::ONE).complete(), &n) != 1 {
             return false
         }
@@ -43,13 +56,25 @@ pub fn fermat_is_prime(n: &Integer, reps: usize) -> bool {
 // For n an odd prime with n-1 = 2^s * r with r odd and a in [1, n-1] we have: 
 //      a^r = 1 (mod n)    or    a^(2^j * r) = -1 (mod n), for j in [0, s-1]
 pub fn rabin_miller_is_prime(n: &Integer, reps: usize) -> bool {
+    rabin_miller_is_prime_with_rng(n, reps, &mut rand_state_from_entropy())
+}
+
+
+// Takes the random state for the witnesses from the caller, e.g. a seeded one for reproducible
+// runs.
+pub fn rabin_miller_is_prime_with_rng(n: &Integer, reps: usize, rng: &mut RandState) -> bool {
+    if let Some(small) = n.to_u128() {
+        if small < PSI_13 {
+            return is_prime_u128(small);
+        }
+    }
+
     if *n == 2 {
         return true;
     } else if *n == 3 {
         return true;
     }
 
-    let mut rng = RandState::new();
     let mut r: Integer = n.clone() - Integer::ONE;
     let mut s = 0;
     while !r.get_bit(0) {
@@ -58,7 +83,7 @@ pub fn rabin_miller_is_prime(n: &Integer, reps: usize) -> bool {
     }
 
     for _ in 0..reps {
-        let a = Integer::from(2) + (n-Integer::from(4)).random_below(&mut rng);
+        let a = Integer::from(2) + (n-Integer::from(4)).random_below(rng);
         let mut y = pow_rug(&a, &r, n);
 
         if &y != Integer::ONE && y != (n-Integer::ONE).complete() {
@@ -81,27 +106,435 @@ pub fn rabin_miller_is_prime(n: &Integer, reps: usize) -> bool {
     true
 }
 
+// The first 13 primes are strong bases which together have no common strong pseudoprime below
+// psi_13 = 3317044064679887385961981 (Sorenson and Webster), which covers every n < 2^64 and
+// somewhat more than 2^81.
+const DETERMINISTIC_BASES: [u64; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+const PSI_13: u128 = 3317044064679887385961981;
+
+
+fn mul_mod_u64(a: u64, b: u64, n: u64) -> u64 {
+    ((a as u128 * b as u128) % n as u128) as u64
+}
+
+
+fn pow_mod_u64(a: u64, mut b: u64, n: u64) -> u64 {
+    let mut product = 1 % n;
+    let mut base = a % n;
+    while b != 0 {
+        if b & 1 == 1 {
+            product = mul_mod_u64(product, base, n);
+        }
+        base = mul_mod_u64(base, base, n);
+        b >>= 1;
+    }
+    product
+}
+
+
+// Trial division by the bases themselves, so that the strong test only sees odd n > 41.
+fn small_base_check(n: u128) -> Option<bool> {
+    for p in DETERMINISTIC_BASES {
+        if n == p as u128 {
+            return Some(true);
+        }
+        if n % p as u128 == 0 {
+            return Some(false);
+        }
+    }
+    if n < 2 {
+        return Some(false);
+    }
+    None
+}
+
+
+pub fn is_prime_u64(n: u64) -> bool {
+    if let Some(is_prime) = small_base_check(n as u128) {
+        return is_prime;
+    }
+
+    let s = (n - 1).trailing_zeros();
+    let r = (n - 1) >> s;
+
+    'witness: for a in DETERMINISTIC_BASES {
+        let mut y = pow_mod_u64(a, r, n);
+        if y == 1 || y == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            y = mul_mod_u64(y, y, n);
+            if y == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+
+// Montgomery arithmetic modulo an odd n < 2^128 with R = 2^128, which needs only 128-bit words
+// and no division after setup.
+struct Montgomery128 {
+    n: u128,
+    // -n^(-1) mod 2^128
+    n_neg_inv: u128,
+    // R^2 mod n
+    r2: u128,
+}
 
+
+impl Montgomery128 {
+    fn new(n: u128) -> Montgomery128 {
+        // Newton iteration doubles the number of correct bits, starting from n*n = 1 (mod 8).
+        let mut inv = n;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u128.wrapping_sub(n.wrapping_mul(inv)));
+        }
+
+        let mut r2 = (u128::MAX % n + 1) % n;
+        for _ in 0..128 {
+            r2 = Self::add_mod(r2, r2, n);
+        }
+
+        Montgomery128 {
+            n,
+            n_neg_inv: inv.wrapping_neg(),
+            r2,
+        }
+    }
+
+    fn add_mod(a: u128, b: u128, n: u128) -> u128 {
+        let (sum, overflow) = a.overflowing_add(b);
+        if overflow || sum >= n {
+            sum.wrapping_sub(n)
+        } else {
+            sum
+        }
+    }
+
+    // Full 256-bit product as (high, low).
+    fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+        let mask = u64::MAX as u128;
+        let (a_hi, a_lo) = (a >> 64, a & mask);
+        let (b_hi, b_lo) = (b >> 64, b & mask);
+
+        let lo_lo = a_lo * b_lo;
+        let hi_lo = a_hi * b_lo;
+        let lo_hi = a_lo * b_hi;
+        let hi_hi = a_hi * b_hi;
+
+        let middle = (lo_lo >> 64) + (hi_lo & mask) + (lo_hi & mask);
+        let low = (middle << 64) | (lo_lo & mask);
+        let high = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (middle >> 64);
+        (high, low)
+    }
+
+    // t*R^(-1) mod n for t = (high, low) < n*R.
+    fn reduce(&self, (high, low): (u128, u128)) -> u128 {
+        let m = low.wrapping_mul(self.n_neg_inv);
+        let (mn_high, mn_low) = Self::mul_wide(m, self.n);
+        let (_, carry) = low.overflowing_add(mn_low);
+        let (t, overflow1) = high.overflowing_add(mn_high);
+        let (t, overflow2) = t.overflowing_add(carry as u128);
+        if overflow1 || overflow2 || t >= self.n {
+            t.wrapping_sub(self.n)
+        } else {
+            t
+        }
+    }
+
+    fn mul(&self, a: u128, b: u128) -> u128 {
+        self.reduce(Self::mul_wide(a, b))
+    }
+
+    fn to_montgomery(&self, a: u128) -> u128 {
+        self.mul(a % self.n, self.r2)
+    }
+
+    fn pow(&self, a: u128, mut b: u128) -> u128 {
+        let mut product = self.to_montgomery(1);
+        let mut base = self.to_montgomery(a);
+        while b != 0 {
+            if b & 1 == 1 {
+                product = self.mul(product, base);
+            }
+            base = self.mul(base, base);
+            b >>= 1;
+        }
+        product
+    }
+}
+
+
+// Exact for n < psi_13, which includes all of u64. Above it no deterministic set of bases is
+// known, and the answer is only as good as 13 strong tests to fixed bases.
+pub fn is_prime_u128(n: u128) -> bool {
+    if n <= u64::MAX as u128 {
+        return is_prime_u64(n as u64);
+    }
+    if let Some(is_prime) = small_base_check(n) {
+        return is_prime;
+    }
+
+    let montgomery = Montgomery128::new(n);
+    let one = montgomery.to_montgomery(1);
+    let minus_one = montgomery.to_montgomery(n - 1);
+    let s = (n - 1).trailing_zeros();
+    let r = (n - 1) >> s;
+
+    'witness: for a in DETERMINISTIC_BASES {
+        let mut y = montgomery.pow(a as u128, r);
+        if y == one || y == minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            y = montgomery.mul(y, y);
+            if y == minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+// Single strong test to the base a, with n - 1 = 2^s * r and r odd.
+pub fn is_strong_probable_prime(n: &Integer, a: &Integer) -> bool {
+    let n_minus_one: Integer = (n - Integer::ONE).complete();
+    let s = n_minus_one.find_one(0).expect("n is odd and larger than 1");
+    let r: Integer = (&n_minus_one >> s).complete();
+
+    let mut y = pow_rug(a, &r, n);
+    if y == 1 || y == n_minus_one {
+        return true;
+    }
+    for _ in 1..s {
+        y = y.square().modulo(n);
+        if y == n_minus_one {
+            return true;
+        }
+    }
+    false
+}
+
+
+// Selfridge's method A: the first D in 5, -7, 9, -11, ... with (D/n) = -1, giving P = 1 and
+// Q = (1 - D)/4. Returns None if n is a perfect square, where no such D exists, or if some D
+// shares a factor with n.
+fn selfridge_parameters(n: &Integer) -> Option<(Integer, Integer)> {
+    if n.is_perfect_square() {
+        return None;
+    }
+    let mut d: Integer = Integer::from(5);
+    loop {
+        match jacobi(&d, n) {
+            -1 => {
+                let q: Integer = (Integer::ONE - &d).complete() / 4;
+                return Some((d, q));
+            },
+            0 if d.clone().abs() != *n => return None,
+            _ => {},
+        }
+        d = if d.is_positive() { -(d + 2u32) } else { -(d - 2u32) };
+    }
+}
+
+
+// Halves x modulo the odd n.
+fn half_mod(x: Integer, n: &Integer) -> Integer {
+    let x = if x.is_odd() { x + n } else { x };
+    (x >> 1u32).modulo(n)
+}
+
+
+// With P = 1, Q and D = P^2 - 4Q from Selfridge's method and n + 1 = 2^s * d with d odd, a prime
+// n satisfies
+//      U_d = 0 (mod n)    or    V_(2^r * d) = 0 (mod n), for r in [0, s-1].
+// The sequences are computed by doubling
+//      U_2k = U_k V_k,    V_2k = V_k^2 - 2Q^k
+// and stepping
+//      U_(k+1) = (P U_k + V_k)/2,    V_(k+1) = (D U_k + P V_k)/2.
+pub fn is_strong_lucas_probable_prime(n: &Integer) -> bool {
+    if *n == 2 {
+        return true;
+    }
+    if *n < 2 || n.is_even() {
+        return false;
+    }
+    let Some((d, q)) = selfridge_parameters(n) else {
+        return false;
+    };
+
+    let n_plus_one: Integer = (n + Integer::ONE).complete();
+    let s = n_plus_one.find_one(0).expect("n + 1 is positive");
+    let k: Integer = (&n_plus_one >> s).complete();
+
+    let mut u: Integer = Integer::from(1);
+    let mut v: Integer = Integer::from(1);
+    let mut q_k: Integer = q.modulo_ref(n).complete();
+
+    for i in (0..k.significant_bits() - 1).rev() {
+        u = (u * &v).modulo(n);
+        v = (v.square() - (&q_k << 1u32).complete()).modulo(n);
+        q_k = q_k.square().modulo(n);
+        if k.get_bit(i) {
+            let u_next = half_mod((&u + &v).complete(), n);
+            v = half_mod((&d * &u).complete() + &v, n);
+            u = u_next;
+            q_k = (q_k * &q).modulo(n);
+        }
+    }
+
+    if u.is_zero() || v.is_zero() {
+        return true;
+    }
+    for _ in 1..s {
+        v = (v.square() - (&q_k << 1u32).complete()).modulo(n);
+        if v.is_zero() {
+            return true;
+        }
+        q_k = q_k.square().modulo(n);
+    }
+    false
+}
+
+
+// Baillie-PSW: a strong test to the base 2 followed by a strong Lucas test. The two tests fail
+// on very different numbers, and no composite passing both is known.
+pub fn baillie_psw_is_prime(n: &Integer) -> bool {
+    if let Some(small) = n.to_u128() {
+        if small < PSI_13 {
+            return is_prime_u128(small);
+        }
+    }
+    if n.is_negative() || n.is_even() {
+        return false;
+    }
+    for p in DETERMINISTIC_BASES {
+        if n.is_divisible_u(p as u32) {
+            return false;
+        }
+    }
+
+    is_strong_probable_prime(n, &Integer::from(2)) && is_strong_lucas_probable_prime(n)
+}
+
+
+// The check used when generating primes: Baillie-PSW, which no known composite passes. Wherever
+// a generator takes t, it is the number of random Rabin-Miller rounds run after Baillie-PSW for
+// extra confidence, so t = 0 is the default and every further round only adds cost.
+fn passes_primality_checks(p: &Integer, t: usize) -> bool {
+    baillie_psw_is_prime(p) && (t == 0 || rabin_miller_is_prime(p, t))
+}
+
+// For p an odd prime, M_p = 2^p - 1 is prime exactly when s_(p-2) = 0 (mod M_p), where
+//      s_0 = 4,    s_(i+1) = s_i^2 - 2.
+pub fn lucas_lehmer_is_prime(p: u32) -> bool {
+    if p == 2 {
+        return true;
+    }
+    if !is_prime_u64(p as u64) {
+        return false;
+    }
+
+    // Adding M_p - 2 instead of subtracting 2 keeps everything non-negative.
+    let minus_two: Integer = (Integer::ONE << p).complete() - 3u32;
+    let mut s: Integer = Integer::from(4);
+    for _ in 0..p - 2 {
+        s = reduce_mod_mersenne(&(s.square() + &minus_two), p);
+    }
+    s.is_zero()
+}
+
+
+// Proth's theorem: N = k*2^n + 1 with k odd and k < 2^n is prime exactly when
+//      a^((N-1)/2) = -1 (mod N)
+// for some a, and any a with (a/N) = -1 will do. Returns None if N is not a Proth number.
+pub fn proth_is_prime(k: &Integer, n: u32) -> Option<bool> {
+    if n == 0 || k.is_even() || !k.is_positive() || k.significant_bits() > n {
+        return None;
+    }
+    let big_n: Integer = (k << n).complete() + 1u32;
+    if big_n.is_perfect_square() {
+        return Some(false);
+    }
+
+    let mut a: Integer = Integer::from(3);
+    loop {
+        match jacobi(&a, &big_n) {
+            -1 => break,
+            0 if a < big_n => return Some(false),
+            _ => a.next_prime_mut(),
+        }
+    }
+
+    // a^(k*2^(n-1)) as a^k followed by n-1 squarings.
+    let barrett = BarrettContext::new(big_n.clone()).expect("N is positive");
+    let mut y = barrett.pow(&a, k);
+    for _ in 0..n - 1 {
+        y = barrett.square(&y);
+    }
+    Some(y == (&big_n - Integer::ONE).complete())
+}
+
+
+// Pépin's test: F_n = 2^(2^n) + 1 with n >= 1 is prime exactly when
+//      3^((F_n - 1)/2) = 3^(2^(2^n - 1)) = -1 (mod F_n).
+// The exponent 2^n has to fit in a u32, so n is at most 31, far beyond what can be computed.
+pub fn pepin_is_prime(n: u32) -> bool {
+    assert!(n < u32::BITS, "Pépin's test needs 2^n to fit in a u32, i.e. n <= 31");
+    if n == 0 {
+        return true;
+    }
+    let m: u32 = 1 << n;
+    let mut y: Integer = Integer::from(3);
+    for _ in 0..m - 1 {
+        y = reduce_mod_fermat(&y.square(), m);
+    }
+    y == (Integer::ONE << m).complete()
+}
+
+
+// Random odd candidates of the given bits until one passes Baillie-PSW and t extra Rabin-Miller
+// rounds, so t = 0 for Baillie-PSW alone.
 pub fn find_prime_with_bit_length(bits: usize, t: usize) -> Integer {
-    let mut p: Integer = randint_bits_odd(bits);
-    while !rabin_miller_is_prime(&p, t) {
-        p = randint_bits_odd(bits);
+    find_prime_with_bit_length_with_rng(bits, t, &mut rand_state_from_entropy())
+}
+
+
+// The candidates are drawn from the given random state, so a seeded one gives the same prime.
+pub fn find_prime_with_bit_length_with_rng(bits: usize, t: usize, rng: &mut RandState) -> Integer {
+    let mut p: Integer = random_odd_bits(bits, rng);
+    while !passes_primality_checks(&p, t) {
+        p = random_odd_bits(bits, rng);
     }
     p
 }
 
 
 pub fn find_prime_with_bit_length_using_trial_division(bits: usize, t: usize, bound: usize) -> Integer {
-    let mut p: Integer = randint_bits_odd(bits);
+    find_prime_with_bit_length_using_trial_division_with_rng(bits, t, bound, &mut rand_state_from_entropy())
+}
+
+
+pub fn find_prime_with_bit_length_using_trial_division_with_rng(bits: usize, t: usize, bound: usize, rng: &mut RandState) -> Integer {
+    let mut p: Integer = random_odd_bits(bits, rng);
     while !is_likely_prime_with_trial_division(&p, t, bound) {
-        p = randint_bits_odd(bits);
+        p = random_odd_bits(bits, rng);
     }
     p
 }
 
 
 pub fn find_prime_with_bit_length_using_interval(bits: usize, d: usize, t: usize, bound: usize) -> Option<Integer> {
-    let mut n = randint_bits(bits);
+    find_prime_with_bit_length_using_interval_with_rng(bits, d, t, bound, &mut rand_state_from_entropy())
+}
+
+
+pub fn find_prime_with_bit_length_using_interval_with_rng(bits: usize, d: usize, t: usize, bound: usize, rng: &mut RandState) -> Option<Integer> {
+    let mut n = random_bits_exact(bits, rng);
     if is_likely_prime_with_trial_division(&n, t, bound) {
         return Some(n)
     }
@@ -117,82 +550,387 @@ pub fn find_prime_with_bit_length_using_interval(bits: usize, d: usize, t: usize
 
 
 pub fn find_prime_in_interval_with_sieving(a: &Integer, d: usize, t: usize, bound: usize) -> Option<Integer> {
-    let small_primes = fs::File::open("small-primes").expect("small-primes file should have been generated");
-    let reader = BufReader::new(small_primes);
+    find_prime_in_interval_with_sieving_with_rng(a, d, t, bound, &mut rand_state_from_entropy())
+}
+
+
+pub fn find_prime_in_interval_with_sieving_with_rng(a: &Integer, d: usize, t: usize, bound: usize, rng: &mut RandState) -> Option<Integer> {
+    find_prime_in_interval_with_table_with_rng(a, d, t, bound, small_primes(), rng)
+}
 
-    let mut vec: Vec<bool> = vec![true; d];
-    let mut capacity = d;
 
-    for prime in reader.lines() {
-        let p = prime.expect("Line should exist").parse::<usize>().expect("Lines should be primes");
-        if p > bound {
-            break;
+pub fn find_prime_in_interval_with_table(a: &Integer, d: usize, t: usize, bound: usize, table: &PrimeTable) -> Option<Integer> {
+    find_prime_in_interval_with_table_with_rng(a, d, t, bound, table, &mut rand_state_from_entropy())
+}
+
+
+// Sieves [a, a + d) by the primes of the table up to bound, then tests the survivors in an order
+// drawn from rng. A tested survivor is swapped out of the candidates, so none is tested twice.
+pub fn find_prime_in_interval_with_table_with_rng(a: &Integer, d: usize, t: usize, bound: usize, table: &PrimeTable, rng: &mut RandState) -> Option<Integer> {
+    let survivors = sieve_progression(a, Integer::ONE, d, bound, table);
+    let mut candidates: Vec<usize> = survivors
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| **b)
+        .map(|(i, _)| i)
+        .collect();
+
+    while !candidates.is_empty() {
+        let index = random_below(&Integer::from(candidates.len()), rng).to_usize().expect("Index is below the length");
+        let i = candidates.swap_remove(index);
+        let p: Integer = (a + i).into();
+        if passes_primality_checks(&p, t) {
+            return Some(p)
         }
+    }
+    None
+}
 
-        let off_set: usize = (p - (a%p).complete()).to_usize().expect("Should be small enough");
-        let mut count = 0;
-        loop {
-            let index: usize = count*p + &off_set;
-            if index >= d {
-                break;
-            }
 
-            if vec[index] {
-                vec[index] = false;
-                capacity -= 1;
+// Values of k sieved at a time when searching the progression a + km.
+const PROGRESSION_WINDOW: u32 = 1 << 12;
+
+
+fn approx_width_in_random_interval_search(bits: usize, probability: f64) -> usize {
+    ( - probability/( (1f64 - 1f64/((bits as f64)*2f64.ln())) ).ln() ) as usize
+}
+
+
+pub fn find_prime_with_bit_length_using_sieving(bits: usize, t: usize, bound: usize) -> Integer {
+    find_prime_with_bit_length_using_sieving_with_rng(bits, t, bound, &mut rand_state_from_entropy())
+}
+
+
+pub fn find_prime_with_bit_length_using_sieving_with_rng(bits: usize, t: usize, bound: usize, rng: &mut RandState) -> Integer {
+    if bound == 0 {
+        return find_prime_with_bit_length_with_rng(bits, t, rng)
+    }
+    let probability = 0.95;
+    let d = approx_width_in_random_interval_search(bits, probability);
+    
+    loop {
+        let a = random_bits_exact(bits, rng);
+        if let Some(p) = find_prime_in_interval_with_sieving_with_rng(&a, d, t, bound, rng) {
+            return p
+        }
+    }
+}
+
+
+
+
+
+// p is safe if q = (p - 1)/2 is prime as well, q then being a Sophie Germain prime.
+pub fn is_safe_prime(p: &Integer) -> bool {
+    if *p < 5 || p.is_even() {
+        return false;
+    }
+    let q: Integer = (p >> 1u32).complete();
+    baillie_psw_is_prime(&q) && baillie_psw_is_prime(p)
+}
+
+
+pub fn find_sophie_germain_prime_in_interval(a: &Integer, d: usize, t: usize, bound: usize) -> Option<Integer> {
+    find_sophie_germain_prime_in_interval_with_table(a, d, t, bound, small_primes())
+}
+
+
+// Double sieve over q in [a, a + d) and p = 2q + 1: for every small prime r both q = 0 and
+// q = (r - 1)/2 (mod r) are removed, the latter being where r divides p. The survivors are
+// first checked by a single strong test of p, which rejects most of them.
+pub fn find_sophie_germain_prime_in_interval_with_table(a: &Integer, d: usize, t: usize, bound: usize, table: &PrimeTable) -> Option<Integer> {
+    let mut vec: Vec<bool> = vec![true; d];
+    let small_start: Option<u64> = a.to_u64();
+
+    for &r in table.up_to(bound) {
+        let residues: &[u32] = if r == 2 { &[0] } else { &[0, (r - 1)/2] };
+        for &residue in residues {
+            let mut index = ((residue + r - a.mod_u(r)) % r) as usize;
+            while index < d {
+                // Neither q = r nor q = (r - 1)/2, where p = r, is removed if the interval
+                // reaches that low.
+                let exempt = small_start.is_some_and(|start| {
+                    let q = start + index as u64;
+                    q == r as u64 || 2*q + 1 == r as u64
+                });
+                if !exempt {
+                    vec[index] = false;
+                }
+                index += r as usize;
             }
-            count += 1;
         }
     }
 
-    let mut sieving_vec: Vec<usize> = Vec::with_capacity(capacity);   
+    let two = Integer::from(2);
+    for (i, _) in vec.iter().enumerate().filter(|(_, b)| **b) {
+        let q: Integer = (a + i).complete();
+        let p: Integer = (&q << 1u32).complete() + 1u32;
+        if q < 2 || (p > 3 && !is_strong_probable_prime(&p, &two)) {
+            continue;
+        }
+        if passes_primality_checks(&q, t) && passes_primality_checks(&p, t) {
+            return Some(q)
+        }
+    }
+    None
+}
+
+
+// The interval width is about the expected gap between Sophie Germain primes of the given size,
+// which by the Hardy-Littlewood conjecture is (ln q)^2/(2 C_2) with C_2 = 0.66016...
+fn approx_width_in_sophie_germain_search(bits: usize) -> usize {
+    let ln_q = (bits as f64)*2f64.ln();
+    (ln_q*ln_q/1.32) as usize + 1
+}
+
+
+pub fn find_sophie_germain_prime_with_bit_length(bits: usize, t: usize, bound: usize) -> Integer {
+    find_sophie_germain_prime_with_bit_length_with_rng(bits, t, bound, &mut rand_state_from_entropy())
+}
+
 
-    for (i, j) in vec.iter().enumerate() {
-        if *j {
-            sieving_vec.push(i);
+pub fn find_sophie_germain_prime_with_bit_length_with_rng(bits: usize, t: usize, bound: usize, rng: &mut RandState) -> Integer {
+    let d = approx_width_in_sophie_germain_search(bits);
+    loop {
+        let a = random_bits_exact(bits, rng);
+        if let Some(q) = find_sophie_germain_prime_in_interval(&a, d, t, bound) {
+            if q.significant_bits() == bits as u32 {
+                return q
+            }
         }
     }
+}
 
-    if sieving_vec.len() == 0 {
-        return None
+
+// Safe prime p = 2q + 1 with exactly the given number of bits, e.g. for the modulus of a
+// Diffie-Hellman group where the subgroup of order q has no small factors.
+pub fn find_safe_prime_with_bit_length(bits: usize, t: usize, bound: usize) -> Integer {
+    find_safe_prime_with_bit_length_with_rng(bits, t, bound, &mut rand_state_from_entropy())
+}
+
+
+pub fn find_safe_prime_with_bit_length_with_rng(bits: usize, t: usize, bound: usize, rng: &mut RandState) -> Integer {
+    assert!(bits >= 3, "There are no safe primes with fewer than 3 bits");
+    let q = find_sophie_germain_prime_with_bit_length_with_rng(bits - 1, t, bound, rng);
+    (q << 1u32) + 1u32
+}
+
+
+// Gordon's algorithm for a strong prime p, where p - 1 has the large prime factor r, p + 1 has
+// the large prime factor s and r - 1 has the large prime factor u:
+//      r = 2iu + 1 for the first i making r prime,
+//      p_0 = 2(s^(r-2) mod r)s - 1,
+//      p = p_0 + 2jrs for the first j making p prime.
+// Then p_0 = 1 (mod r) and p_0 = -1 (mod s). Returns (p, r, s, u).
+pub fn gordon_strong_prime(bits: usize, t: usize, bound: usize) -> (Integer, Integer, Integer, Integer) {
+    gordon_strong_prime_with_rng(bits, t, bound, &mut rand_state_from_entropy())
+}
+
+
+pub fn gordon_strong_prime_with_rng(bits: usize, t: usize, bound: usize, rng: &mut RandState) -> (Integer, Integer, Integer, Integer) {
+    assert!(bits >= 64, "Strong primes need at least 64 bits");
+    // s and r take up about half of p each, leaving 10 bits for j, and u leaves 8 bits for i.
+    let half = (bits - 10)/2;
+    let s = find_prime_with_bit_length_using_trial_division_with_rng(half, t, bound, rng);
+    let u = find_prime_with_bit_length_using_trial_division_with_rng(half - 8, t, bound, rng);
+
+    let two_u: Integer = (&u << 1u32).complete();
+    let mut r: Integer = (Integer::ONE << (half as u32 - 1)).complete().div_ceil(two_u.clone())*&two_u + 1u32;
+    while !is_likely_prime_with_trial_division(&r, t, bound) {
+        r += &two_u;
     }
 
-    let mut rng = thread_rng();
-    let mut index = rng.gen_range(0..capacity);
-    let mut p: Integer = (a + sieving_vec[index]).into();
+    let exponent: Integer = (&r - 2u32).complete();
+    let p_0: Integer = Integer::from(2)*pow_rug(&s, &exponent, &r)*&s - 1u32;
+    let step: Integer = Integer::from(2)*&r*&s;
+    let lower: Integer = (Integer::ONE << (bits as u32 - 1)).complete();
+    let upper: Integer = (Integer::ONE << bits as u32).complete();
 
-    while !rabin_miller_is_prime(&p, t) {
-        sieving_vec.remove(index);
-        if sieving_vec.len() == 0 {
-            return None
+    // Starts from a random j so that repeated calls with the same s and r differ.
+    let j_range: Integer = (&upper - &lower).complete() / &step;
+    let mut p: Integer = p_0 + (lower.clone().div_ceil(step.clone()) + random_below(&(j_range/2u32 + 1u32), rng))*&step;
+    loop {
+        if p >= upper {
+            return gordon_strong_prime_with_rng(bits, t, bound, rng)
         }
-        capacity -= 1;
-        p = (a + sieving_vec[rng.gen_range(0..capacity)]).into();
-        index = rng.gen_range(0..capacity);
+        if is_likely_prime_with_trial_division(&p, t, bound) {
+            return (p, r, s, u)
+        }
+        p += &step;
     }
-    Some(p)
 }
 
 
-fn approx_width_in_random_interval_search(bits: usize, probability: f64) -> usize {
-    ( - probability/( (1f64 - 1f64/((bits as f64)*2f64.ln())) ).ln() ) as usize
+pub fn find_strong_prime_with_bit_length(bits: usize, t: usize, bound: usize) -> Integer {
+    gordon_strong_prime(bits, t, bound).0
 }
 
 
-pub fn find_prime_with_bit_length_using_sieving(bits: usize, t: usize, bound: usize) -> Integer {
-    if bound == 0 {
-        find_prime_with_bit_length(bits, t);
+pub fn find_strong_prime_with_bit_length_with_rng(bits: usize, t: usize, bound: usize, rng: &mut RandState) -> Integer {
+    gordon_strong_prime_with_rng(bits, t, bound, rng).0
+}
+
+
+// Marks the i in [0, width) for which a_0 + i*m has no prime factor r <= bound from the table,
+// using that r | a_0 + i*m exactly when i = -a_0 m^(-1) (mod r). Primes dividing m never divide
+// the progression when gcd(a_0, m) = 1.
+fn sieve_progression(a_0: &Integer, m: &Integer, width: usize, bound: usize, table: &PrimeTable) -> Vec<bool> {
+    let mut vec: Vec<bool> = vec![true; width];
+    let small_start: Option<u64> = a_0.to_u64();
+    let small_step: Option<u64> = m.to_u64();
+
+    for &r in table.up_to(bound) {
+        let m_r = m.mod_u(r) as u64;
+        if m_r == 0 {
+            continue;
+        }
+        let r_64 = r as u64;
+        let inverse = pow_mod_u64(m_r, r_64 - 2, r_64);
+        let mut index = (((r_64 - a_0.mod_u(r) as u64) % r_64)*inverse % r_64) as usize;
+        while index < width {
+            // r itself is not removed if the progression passes through it.
+            let exempt = small_start.zip(small_step).is_some_and(|(start, step)| {
+                (index as u64).checked_mul(step).and_then(|x| x.checked_add(start)) == Some(r_64)
+            });
+            if !exempt {
+                vec[index] = false;
+            }
+            index += r as usize;
+        }
     }
-    let probability = 0.95;
-    let d = approx_width_in_random_interval_search(bits, probability);
-    
+    vec
+}
+
+
+pub fn find_prime_congruent_with_bit_length(bits: usize, a: &Integer, m: &Integer, t: usize, bound: usize) -> Option<Integer> {
+    find_prime_congruent_with_table(bits, a, m, t, bound, small_primes())
+}
+
+
+pub fn find_prime_congruent_with_bit_length_with_rng(bits: usize, a: &Integer, m: &Integer, t: usize, bound: usize, rng: &mut RandState) -> Option<Integer> {
+    find_prime_congruent_with_table_with_rng(bits, a, m, t, bound, small_primes(), rng)
+}
+
+
+pub fn find_prime_congruent_with_table(bits: usize, a: &Integer, m: &Integer, t: usize, bound: usize, table: &PrimeTable) -> Option<Integer> {
+    find_prime_congruent_with_table_with_rng(bits, a, m, t, bound, table, &mut rand_state_from_entropy())
+}
+
+
+// A prime p = a (mod m) with the given number of bits, found along the progression p = a + km by
+// sieving windows of PROGRESSION_WINDOW values of k from a random start. Returns None if
+// gcd(a, m) > 1, or if the progression has fewer values of that size than a window and none of
+// them is prime.
+pub fn find_prime_congruent_with_table_with_rng(bits: usize, a: &Integer, m: &Integer, t: usize, bound: usize, table: &PrimeTable, rng: &mut RandState) -> Option<Integer> {
+    assert!(*m > 0, "The modulus must be positive");
+    assert!(bits >= 2, "There are no primes with fewer than 2 bits");
+    let a: Integer = a.modulo_ref(m).complete();
+    if a.gcd_ref(m).complete() != 1 {
+        return None
+    }
+
+    let lower: Integer = (Integer::ONE << (bits as u32 - 1)).complete();
+    let upper: Integer = (Integer::ONE << bits as u32).complete();
+    let k_start: Integer = (lower - &a).div_ceil(m.clone()).max(Integer::ZERO.clone());
+    let k_end: Integer = (upper - Integer::ONE - &a).div_floor(m.clone()) + 1u32;
+    if k_end <= k_start {
+        return None
+    }
+    let count: Integer = k_end - &k_start;
+
+    if count <= PROGRESSION_WINDOW {
+        let width = count.to_usize().expect("Count is at most the window");
+        let a_0: Integer = a + k_start*m;
+        let survivors = sieve_progression(&a_0, m, width, bound, table);
+        let offset = random_below(&Integer::from(width), rng).to_usize().expect("Offset is below width");
+        return (0..width)
+            .map(|i| (i + offset) % width)
+            .filter(|i| survivors[*i])
+            .map(|i| &a_0 + (m*i).complete())
+            .find(|p| passes_primality_checks(p, t))
+    }
+
     loop {
-        let a = randint_bits(bits);
-        if let Some(p) = find_prime_in_interval_with_sieving(&a, d, t, bound) {
-            return p
+        let span: Integer = (&count - PROGRESSION_WINDOW).complete() + 1u32;
+        let k: Integer = &k_start + random_below(&span, rng);
+        let a_0: Integer = &a + k*m;
+        let survivors = sieve_progression(&a_0, m, PROGRESSION_WINDOW as usize, bound, table);
+        for (i, _) in survivors.iter().enumerate().filter(|(_, b)| **b) {
+            let p: Integer = &a_0 + (m*i).complete();
+            if passes_primality_checks(&p, t) {
+                return Some(p)
+            }
         }
     }
 }
 
 
+// Primes p = 3 (mod 4), as used for Blum integers and the Rabin cryptosystem, where square roots
+// modulo p are found as a^((p+1)/4).
+pub fn find_blum_prime_with_bit_length(bits: usize, t: usize, bound: usize) -> Integer {
+    assert!(bits >= 2, "There are no primes with fewer than 2 bits");
+    find_prime_congruent_with_bit_length(bits, &Integer::from(3), &Integer::from(4), t, bound)
+        .expect("There are primes 3 mod 4 of every bit length from 2")
+}
+
+
+// Primes p = 1 (mod 2^k), so that Z/pZ has 2^k-th roots of unity for number theoretic transforms
+// of length up to 2^k. Returns None if there is no such prime, e.g. if k >= bits.
+pub fn find_ntt_prime_with_bit_length(bits: usize, k: u32, t: usize, bound: usize) -> Option<Integer> {
+    find_prime_congruent_with_bit_length(bits, Integer::ONE, &(Integer::ONE << k).complete(), t, bound)
+}
+
+
+// A prime p with the given number of bits together with the factorization of p - 1, so that
+// e.g. a primitive root is found without factoring. p - 1 = 2 q_1 ... q_n is built from random
+// primes q_i of about bits/4 bits, the last of which is chosen to give p the right size.
+pub fn find_prime_with_factored_order(bits: usize, t: usize, bound: usize) -> (Integer, Vec<(Integer, u32)>) {
+    find_prime_with_factored_order_with_rng(bits, t, bound, &mut rand_state_from_entropy())
+}
+
+
+pub fn find_prime_with_factored_order_with_rng(bits: usize, t: usize, bound: usize, rng: &mut RandState) -> (Integer, Vec<(Integer, u32)>) {
+    assert!(bits >= 16, "Primes with factored order need at least 16 bits");
+    let factor_bits = (bits/4).clamp(4, 128);
+    let lower: Integer = (Integer::ONE << (bits as u32 - 1)).complete();
+    let upper: Integer = (Integer::ONE << bits as u32).complete();
+
+    loop {
+        let mut product: Integer = Integer::from(2);
+        let mut factors: Vec<Integer> = vec![Integer::from(2)];
+        while bits - product.significant_bits() as usize > 2*factor_bits {
+            let q = find_prime_with_bit_length_with_rng(factor_bits, t, rng);
+            product *= &q;
+            factors.push(q);
+        }
 
+        // The last factor q must satisfy lower <= product*q + 1 < upper.
+        let q_min: Integer = (&lower - Integer::ONE).complete().div_ceil(product.clone());
+        let q_max: Integer = (&upper - 2u32).complete().div_floor(product.clone());
+        if q_max < q_min {
+            continue;
+        }
+        for _ in 0..2*bits {
+            let span: Integer = (&q_max - &q_min).complete() + 1u32;
+            let start: Integer = &q_min + random_below(&span, rng);
+            let q = next_prime(&(start - 1u32));
+            if q > q_max {
+                continue;
+            }
+            let p: Integer = (&product*&q).complete() + 1u32;
+            if has_factor_in_table(&p, bound, small_primes()) {
+                continue;
+            }
+            if passes_primality_checks(&p, t) {
+                factors.push(q);
+                factors.sort();
+                let factors = factors
+                    .into_iter()
+                    .dedup_with_count()
+                    .map(|(count, q)| (q, count as u32))
+                    .collect();
+                return (p, factors)
+            }
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use rug::integer::Order;
+use rug::{Complete, Integer};
+
+
+// log2(n) for n > 0, also for n too large for an f64.
+fn log2(n: &Integer) -> f64 {
+    let (mantissa, exponent) = n.to_f64_exp();
+    exponent as f64 + mantissa.log2()
+}
+
+
+// Whether the order of n modulo r exceeds the bound, i.e. n^k != 1 (mod r) for all k <= bound.
+fn order_exceeds(n_mod_r: u64, r: u64, bound: u64) -> bool {
+    let mut power = 1;
+    for _ in 0..bound {
+        power = power * n_mod_r % r;
+        if power == 1 {
+            return false;
+        }
+    }
+    true
+}
+
+
+fn euler_totient_u64(mut r: u64) -> u64 {
+    let mut phi = r;
+    let mut p = 2;
+    while p*p <= r {
+        if r.is_multiple_of(p) {
+            while r.is_multiple_of(p) {
+                r /= p;
+            }
+            phi -= phi/p;
+        }
+        p += 1;
+    }
+    if r > 1 {
+        phi -= phi/r;
+    }
+    phi
+}
+
+
+// Square of a polynomial of degree below r in (Z/nZ)[x]/(x^r - 1) by Kronecker substitution: the
+// coefficients are packed into one integer with slots of `words` 64-bit words each, wide enough
+// for the r(n - 1)^2 a coefficient of the square can reach, so that a single integer
+// multiplication does all the work.
+fn square_mod(a: &[Integer], n: &Integer, words: usize) -> Vec<Integer> {
+    let mut digits: Vec<u64> = vec![0; a.len()*words];
+    for (i, c) in a.iter().enumerate() {
+        match c.to_u64() {
+            Some(word) => digits[i*words] = word,
+            None => {
+                let limbs = c.to_digits::<u64>(Order::Lsf);
+                digits[i*words..i*words + limbs.len()].copy_from_slice(&limbs);
+            }
+        }
+    }
+    let product: Integer = Integer::from_digits(&digits, Order::Lsf).square();
+
+    // Slot i + r folds onto slot i, as x^r = 1.
+    let r = a.len();
+    let mut result: Vec<Integer> = vec![Integer::ZERO.clone(); r];
+    let mut high: Integer = Integer::new();
+    for (i, slot) in product.to_digits::<u64>(Order::Lsf).chunks(words).enumerate() {
+        if i < r {
+            result[i].assign_digits(slot, Order::Lsf);
+        } else {
+            high.assign_digits(slot, Order::Lsf);
+            result[i - r] += &high;
+        }
+    }
+    for c in result.iter_mut() {
+        *c %= n;
+    }
+    result
+}
+
+
+// (x + a)^n in (Z/nZ)[x]/(x^r - 1) by square and multiply, where multiplying by x + a only
+// shifts and scales.
+fn pow_linear(a: u64, n: &Integer, r: usize, words: usize) -> Vec<Integer> {
+    let mut result: Vec<Integer> = vec![Integer::ZERO.clone(); r];
+    result[0] = Integer::from(1);
+    for i in (0..n.significant_bits()).rev() {
+        result = square_mod(&result, n, words);
+        if n.get_bit(i) {
+            let mut shifted: Vec<Integer> = result.clone();
+            shifted.rotate_right(1);
+            for (c, s) in result.iter_mut().zip(shifted) {
+                *c *= a;
+                *c += s;
+                *c %= n;
+            }
+        }
+    }
+    result
+}
+
+
+// x*y mod n, avoiding the slow 128-bit division whenever the product fits in a word.
+fn mul_mod_word(x: u64, y: u64, n: u64) -> u64 {
+    if (x | y) >> 32 == 0 {
+        x*y % n
+    } else {
+        (x as u128*y as u128 % n as u128) as u64
+    }
+}
+
+
+fn add_mod_word(x: u64, y: u64, n: u64) -> u64 {
+    let (sum, overflow) = x.overflowing_add(y);
+    if overflow || sum >= n { sum.wrapping_sub(n) } else { sum }
+}
+
+
+// The same for n < 2^64, where the slots are reduced with word arithmetic using 2^(64j) mod n.
+fn square_mod_u64(a: &[u64], n: u64, words: usize) -> Vec<u64> {
+    let mut digits: Vec<u64> = vec![0; a.len()*words];
+    for (i, c) in a.iter().enumerate() {
+        digits[i*words] = *c;
+    }
+    let product: Integer = Integer::from_digits(&digits, Order::Lsf).square();
+
+    let mut word_powers: Vec<u64> = vec![1 % n];
+    for j in 1..words {
+        word_powers.push(((word_powers[j - 1] as u128) << 64).rem_euclid(n as u128) as u64);
+    }
+    let r = a.len();
+    let mut result: Vec<u64> = vec![0; r];
+    for (i, slot) in product.to_digits::<u64>(Order::Lsf).chunks(words).enumerate() {
+        let value = slot
+            .iter()
+            .zip(&word_powers)
+            .fold(0, |sum, (word, power)| add_mod_word(sum, mul_mod_word(word % n, *power, n), n));
+        result[i % r] = add_mod_word(result[i % r], value, n);
+    }
+    result
+}
+
+
+fn pow_linear_u64(a: u64, n: u64, r: usize, words: usize) -> Vec<u64> {
+    let a = a % n;
+    let mut result: Vec<u64> = vec![0; r];
+    result[0] = 1 % n;
+    for i in (0..64 - n.leading_zeros()).rev() {
+        result = square_mod_u64(&result, n, words);
+        if (n >> i) & 1 == 1 {
+            let mut previous = result[r - 1];
+            for c in result.iter_mut() {
+                let value = add_mod_word(mul_mod_word(*c, a, n), previous, n);
+                previous = *c;
+                *c = value;
+            }
+        }
+    }
+    result
+}
+
+
+// The test of Agrawal, Kayal and Saxena, which is deterministic and polynomial in log n but far
+// slower than Rabin-Miller or Baillie-PSW. For n that is not a perfect power, with r the least
+// number such that the order of n modulo r exceeds log2(n)^2, n is prime exactly when
+//      n has no factor a <= r, and n <= r or
+//      (x + a)^n = x^n + a    in (Z/nZ)[x]/(x^r - 1)    for 1 <= a <= sqrt(phi(r)) log2(n).
+pub fn aks_is_prime(n: &Integer) -> bool {
+    if *n < 2 {
+        return false;
+    }
+    if n.is_perfect_power() {
+        return false;
+    }
+
+    let log_n = log2(n);
+    let order_bound = (log_n*log_n).floor() as u64;
+    let mut r: u64 = 2;
+    while !order_exceeds(n.mod_u(r as u32) as u64, r, order_bound) {
+        r += 1;
+    }
+
+    let mut a: u64 = 2;
+    while a <= r && *n > a {
+        let gcd: Integer = n.gcd_u_ref(a as u32).complete();
+        if gcd > 1 {
+            return false;
+        }
+        a += 1;
+    }
+    if *n <= r {
+        return true;
+    }
+
+    let limit = ((euler_totient_u64(r) as f64).sqrt()*log_n).floor() as u64;
+    match n.to_u64() {
+        Some(small) => passes_congruences_u64(small, r as usize, limit),
+        None => passes_congruences(n, r as usize, limit),
+    }
+}
+
+
+// Slots of a square in (Z/nZ)[x]/(x^r - 1) hold sums of r products below n^2.
+fn slot_words(n: &Integer, r: usize) -> usize {
+    ((2*n.significant_bits() + r.ilog2() + 1) as usize).div_ceil(64)
+}
+
+
+// (x + a)^n = x^n + a in (Z/nZ)[x]/(x^r - 1) for 1 <= a <= limit.
+fn passes_congruences(n: &Integer, r: usize, limit: u64) -> bool {
+    let words = slot_words(n, r);
+    let n_mod_r = n.mod_u(r as u32) as usize;
+    (1..=limit).all(|a| {
+        let mut rhs: Vec<Integer> = vec![Integer::ZERO.clone(); r];
+        rhs[0] = Integer::from(a) % n;
+        rhs[n_mod_r] += 1u32;
+        rhs[n_mod_r] %= n;
+        pow_linear(a, n, r, words) == rhs
+    })
+}
+
+
+fn passes_congruences_u64(n: u64, r: usize, limit: u64) -> bool {
+    let words = slot_words(&Integer::from(n), r);
+    let n_mod_r = (n % r as u64) as usize;
+    (1..=limit).all(|a| {
+        let mut rhs: Vec<u64> = vec![0; r];
+        rhs[0] = a % n;
+        rhs[n_mod_r] = (rhs[n_mod_r] + 1) % n;
+        pow_linear_u64(a, n, r, words) == rhs
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rug::Integer;
+    use super::*;
+
+    // The multi-word path only runs on n >= 2^64 by itself, so it is checked against the word
+    // path on small n, where both must agree on every power and on every verdict.
+    #[test]
+    fn test_multi_word_path_agrees_with_word_path() {
+        for (n, r) in [(2u64, 3usize), (7, 5), (31, 29), (97, 101), (561, 103), (1009, 211), (4294967291, 113)] {
+            let big = Integer::from(n);
+            let words = slot_words(&big, r);
+            for a in [1u64, 2, 3, 17] {
+                let expected: Vec<Integer> = pow_linear_u64(a, n, r, words).into_iter().map(Integer::from).collect();
+                assert_eq!(pow_linear(a, &big, r, words), expected, "(x + {})^{} mod x^{} - 1", a, n, r);
+            }
+            assert_eq!(passes_congruences(&big, r, 20), passes_congruences_u64(n, r, 20), "Disagree on {}", n);
+        }
+        assert!(passes_congruences(&Integer::from(1000003), 401, 30));
+        assert!(!passes_congruences(&Integer::from(1000009), 401, 30));
+    }
+}
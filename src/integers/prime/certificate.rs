@@ -0,0 +1,424 @@
+use std::fmt;
+use std::str::{FromStr, SplitWhitespace};
+use rug::{ops::Pow, Complete, Integer};
+use crate::integers::arith::jacobi;
+use crate::integers::integer_computations::pow_rug;
+use super::{baillie_psw_is_prime, half_mod};
+
+
+// Below this bound a prime is proven by trial division alone.
+const SMALL_BOUND: u64 = 1 << 32;
+// Pratt certificates need all of n - 1 factored, so they are only attempted for word-sized n.
+const PRATT_BOUND: u128 = 1 << 64;
+// Trial division bound when splitting n - 1 or n + 1 into prime powers.
+const TRIAL_DIVISION_BOUND: u32 = 1 << 20;
+// Number of bases, or Lucas parameters, tried before giving up on a certificate.
+const WITNESS_ATTEMPTS: u32 = 1000;
+
+
+// A proof that n is prime, which can be checked with verify() without trusting whoever made it.
+// Every prime factor used by a proof carries its own certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrimalityCertificate {
+    // n < 2^32, checked by trial division up to sqrt(n).
+    Small(Integer),
+    // n - 1 = prod q^e is fully factored and the witness has order n - 1 modulo n.
+    Pratt {
+        n: Integer,
+        witness: Integer,
+        factors: Vec<(PrimalityCertificate, u32)>,
+    },
+    // Pocklington-Lehmer: F = prod q^e divides n - 1 with F^2 > n, and for every q the witness a
+    // satisfies a^(n-1) = 1 (mod n) and gcd(a^((n-1)/q) - 1, n) = 1.
+    Pocklington {
+        n: Integer,
+        factors: Vec<(PrimalityCertificate, u32, Integer)>,
+    },
+    // Morrison: F = prod q^e divides n + 1 with (F - 1)^2 > n, and the Lucas sequence U of (P, Q)
+    // with (P^2 - 4Q / n) = -1 satisfies U_(n+1) = 0 (mod n) and gcd(U_((n+1)/q), n) = 1.
+    Morrison {
+        n: Integer,
+        p: Integer,
+        q: Integer,
+        factors: Vec<(PrimalityCertificate, u32)>,
+    },
+}
+
+
+impl PrimalityCertificate {
+    pub fn get_prime(&self) -> &Integer {
+        match self {
+            PrimalityCertificate::Small(n) => n,
+            PrimalityCertificate::Pratt { n, .. } => n,
+            PrimalityCertificate::Pocklington { n, .. } => n,
+            PrimalityCertificate::Morrison { n, .. } => n,
+        }
+    }
+
+
+    pub fn verify(&self) -> bool {
+        match self {
+            PrimalityCertificate::Small(n) => verify_small(n),
+            PrimalityCertificate::Pratt { n, witness, factors } => verify_pratt(n, witness, factors),
+            PrimalityCertificate::Pocklington { n, factors } => verify_pocklington(n, factors),
+            PrimalityCertificate::Morrison { n, p, q, factors } => verify_morrison(n, p, q, factors),
+        }
+    }
+}
+
+
+fn verify_small(n: &Integer) -> bool {
+    let Some(n) = n.to_u64() else {
+        return false;
+    };
+    if !(2..SMALL_BOUND).contains(&n) {
+        return false;
+    }
+    let mut d = 2;
+    while d * d <= n {
+        if n % d == 0 {
+            return false;
+        }
+        d += 1;
+    }
+    true
+}
+
+
+// Product of the proven prime powers, or None if some certificate does not hold.
+fn verified_product<'a, I>(factors: I) -> Option<Integer>
+where
+    I: Iterator<Item = (&'a PrimalityCertificate, u32)>
+{
+    let mut product: Integer = Integer::ONE.clone();
+    for (certificate, exponent) in factors {
+        if exponent == 0 || !certificate.verify() {
+            return None;
+        }
+        product *= certificate.get_prime().clone().pow(exponent);
+    }
+    Some(product)
+}
+
+
+fn verify_pratt(n: &Integer, witness: &Integer, factors: &[(PrimalityCertificate, u32)]) -> bool {
+    if *n < 3 {
+        return false;
+    }
+    let n_minus_one: Integer = (n - Integer::ONE).complete();
+    let Some(product) = verified_product(factors.iter().map(|(c, e)| (c, *e))) else {
+        return false;
+    };
+    if product != n_minus_one || pow_rug(witness, &n_minus_one, n) != 1 {
+        return false;
+    }
+    factors
+        .iter()
+        .all(|(c, _)| pow_rug(witness, &(&n_minus_one / c.get_prime()).complete(), n) != 1)
+}
+
+
+fn verify_pocklington(n: &Integer, factors: &[(PrimalityCertificate, u32, Integer)]) -> bool {
+    if *n < 3 {
+        return false;
+    }
+    let n_minus_one: Integer = (n - Integer::ONE).complete();
+    let Some(product) = verified_product(factors.iter().map(|(c, e, _)| (c, *e))) else {
+        return false;
+    };
+    if !n_minus_one.is_divisible(&product) || product.square_ref().complete() <= *n {
+        return false;
+    }
+    factors.iter().all(|(c, _, a)| {
+        pow_rug(a, &n_minus_one, n) == 1
+            && (pow_rug(a, &(&n_minus_one / c.get_prime()).complete(), n) - Integer::ONE).gcd(n) == 1
+    })
+}
+
+
+fn verify_morrison(n: &Integer, p: &Integer, q: &Integer, factors: &[(PrimalityCertificate, u32)]) -> bool {
+    if *n < 3 || n.is_even() {
+        return false;
+    }
+    let n_plus_one: Integer = (n + Integer::ONE).complete();
+    let Some(product) = verified_product(factors.iter().map(|(c, e)| (c, *e))) else {
+        return false;
+    };
+    let d: Integer = p.square_ref().complete() - (q * 4u32).complete();
+    if !n_plus_one.is_divisible(&product) || (product - Integer::ONE).square() <= *n {
+        return false;
+    }
+    if jacobi(&d, n) != -1 || q.gcd_ref(n).complete() != 1 || !lucas_u(p, q, &n_plus_one, n).is_zero() {
+        return false;
+    }
+    factors
+        .iter()
+        .all(|(c, _)| lucas_u(p, q, &(&n_plus_one / c.get_prime()).complete(), n).gcd(n) == 1)
+}
+
+
+// U_k of the Lucas sequence with parameters (P, Q) modulo the odd n, by the same doubling steps
+// as the strong Lucas test.
+fn lucas_u(p: &Integer, q: &Integer, k: &Integer, n: &Integer) -> Integer {
+    if k.is_zero() {
+        return Integer::ZERO.clone();
+    }
+    let d: Integer = p.square_ref().complete() - (q * 4u32).complete();
+    let mut u: Integer = Integer::from(1);
+    let mut v: Integer = p.modulo_ref(n).complete();
+    let mut q_k: Integer = q.modulo_ref(n).complete();
+
+    for i in (0..k.significant_bits() - 1).rev() {
+        u = (u * &v).modulo(n);
+        v = (v.square() - (&q_k << 1u32).complete()).modulo(n);
+        q_k = q_k.square().modulo(n);
+        if k.get_bit(i) {
+            let u_next = half_mod((p * &u).complete() + &v, n);
+            v = half_mod((&d * &u).complete() + (p * &v).complete(), n);
+            u = u_next;
+            q_k = (q_k * q).modulo(n);
+        }
+    }
+    u.modulo(n)
+}
+
+
+// Splits m into prime powers by trial division up to TRIAL_DIVISION_BOUND. A leftover cofactor
+// that passes Baillie-PSW is certified on its own, anything else is returned unfactored.
+fn partial_factorization(m: &Integer) -> (Vec<(PrimalityCertificate, u32)>, Integer) {
+    let mut rest: Integer = m.clone();
+    let mut factors: Vec<(PrimalityCertificate, u32)> = Vec::new();
+
+    let mut d: u32 = 2;
+    while d < TRIAL_DIVISION_BOUND && Integer::from(d).square() <= rest {
+        let mut exponent = 0;
+        while rest.is_divisible_u(d) {
+            rest.div_exact_u_mut(d);
+            exponent += 1;
+        }
+        if exponent > 0 {
+            factors.push((PrimalityCertificate::Small(Integer::from(d)), exponent));
+        }
+        d += if d == 2 { 1 } else { 2 };
+    }
+
+    if rest > 1 && baillie_psw_is_prime(&rest) {
+        if let Some(certificate) = certify_prime(&rest) {
+            factors.push((certificate, 1));
+            rest = Integer::ONE.clone();
+        }
+    }
+    (factors, rest)
+}
+
+
+pub fn pratt_certificate(n: &Integer) -> Option<PrimalityCertificate> {
+    if *n < 3 || n.to_u128().is_none_or(|small| small >= PRATT_BOUND) {
+        return None;
+    }
+    let n_minus_one: Integer = (n - Integer::ONE).complete();
+    let (factors, rest) = partial_factorization(&n_minus_one);
+    if rest != 1 {
+        return None;
+    }
+
+    for a in 2..WITNESS_ATTEMPTS + 2 {
+        let witness = Integer::from(a);
+        if pow_rug(&witness, &n_minus_one, n) != 1 {
+            return None;
+        }
+        if factors.iter().all(|(c, _)| pow_rug(&witness, &(&n_minus_one / c.get_prime()).complete(), n) != 1) {
+            return Some(PrimalityCertificate::Pratt { n: n.clone(), witness, factors });
+        }
+    }
+    None
+}
+
+
+pub fn pocklington_certificate(n: &Integer) -> Option<PrimalityCertificate> {
+    if *n < 3 {
+        return None;
+    }
+    let n_minus_one: Integer = (n - Integer::ONE).complete();
+    let (factors, rest) = partial_factorization(&n_minus_one);
+    let product: Integer = (&n_minus_one / &rest).complete();
+    if product.square() <= *n {
+        return None;
+    }
+
+    let mut witnessed: Vec<(PrimalityCertificate, u32, Integer)> = Vec::with_capacity(factors.len());
+    for (certificate, exponent) in factors {
+        let cofactor: Integer = (&n_minus_one / certificate.get_prime()).complete();
+        let witness = (2..WITNESS_ATTEMPTS + 2)
+            .map(Integer::from)
+            .find(|a| (pow_rug(a, &cofactor, n) - Integer::ONE).gcd(n) == 1)?;
+        if pow_rug(&witness, &n_minus_one, n) != 1 {
+            return None;
+        }
+        witnessed.push((certificate, exponent, witness));
+    }
+    Some(PrimalityCertificate::Pocklington { n: n.clone(), factors: witnessed })
+}
+
+
+pub fn morrison_certificate(n: &Integer) -> Option<PrimalityCertificate> {
+    if *n < 3 || n.is_even() {
+        return None;
+    }
+    let n_plus_one: Integer = (n + Integer::ONE).complete();
+    let (factors, rest) = partial_factorization(&n_plus_one);
+    let product: Integer = (&n_plus_one / &rest).complete();
+    if (product - Integer::ONE).square() <= *n {
+        return None;
+    }
+
+    // Q = -1 and D = P^2 + 4, so only P has to be searched for.
+    let q: Integer = Integer::from(-1);
+    for p in 1..WITNESS_ATTEMPTS + 1 {
+        let p = Integer::from(p);
+        let d: Integer = p.square_ref().complete() + 4u32;
+        if jacobi(&d, n) != -1 {
+            continue;
+        }
+        if !lucas_u(&p, &q, &n_plus_one, n).is_zero() {
+            return None;
+        }
+        if factors.iter().all(|(c, _)| lucas_u(&p, &q, &(&n_plus_one / c.get_prime()).complete(), n).gcd(n) == 1) {
+            return Some(PrimalityCertificate::Morrison { n: n.clone(), p, q, factors });
+        }
+    }
+    None
+}
+
+
+// Tries the cheapest applicable proof first. Returns None if n is composite, or if neither n - 1
+// nor n + 1 is smooth enough to factor far enough by trial division.
+pub fn certify_prime(n: &Integer) -> Option<PrimalityCertificate> {
+    if *n < SMALL_BOUND {
+        return verify_small(n).then(|| PrimalityCertificate::Small(n.clone()));
+    }
+    if !baillie_psw_is_prime(n) {
+        return None;
+    }
+    pratt_certificate(n)
+        .or_else(|| pocklington_certificate(n))
+        .or_else(|| morrison_certificate(n))
+}
+
+
+// Certificates are written as whitespace separated tokens, one line per certificate and one
+// line per factor in front of the certificate of that factor:
+//      small <n>
+//      pratt <n> <witness> <number of factors>
+//      pocklington <n> <number of factors>
+//      morrison <n> <P> <Q> <number of factors>
+//      factor <exponent> [<witness>]
+impl fmt::Display for PrimalityCertificate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PrimalityCertificate::Small(n) => write!(f, "small {}", n),
+            PrimalityCertificate::Pratt { n, witness, factors } => {
+                write!(f, "pratt {} {} {}", n, witness, factors.len())?;
+                for (certificate, exponent) in factors {
+                    write!(f, "\nfactor {}\n{}", exponent, certificate)?;
+                }
+                Ok(())
+            },
+            PrimalityCertificate::Pocklington { n, factors } => {
+                write!(f, "pocklington {} {}", n, factors.len())?;
+                for (certificate, exponent, witness) in factors {
+                    write!(f, "\nfactor {} {}\n{}", exponent, witness, certificate)?;
+                }
+                Ok(())
+            },
+            PrimalityCertificate::Morrison { n, p, q, factors } => {
+                write!(f, "morrison {} {} {} {}", n, p, q, factors.len())?;
+                for (certificate, exponent) in factors {
+                    write!(f, "\nfactor {}\n{}", exponent, certificate)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+
+fn next_token<'a>(tokens: &mut SplitWhitespace<'a>) -> Result<&'a str, String> {
+    tokens.next().ok_or("Unexpected end of certificate".to_string())
+}
+
+
+fn next_integer(tokens: &mut SplitWhitespace) -> Result<Integer, String> {
+    let token = next_token(tokens)?;
+    Integer::from_str(token).map_err(|e| format!("Invalid integer {}: {}", token, e))
+}
+
+
+fn next_number<T: FromStr>(tokens: &mut SplitWhitespace) -> Result<T, String> {
+    let token = next_token(tokens)?;
+    token.parse::<T>().map_err(|_| format!("Invalid count {}", token))
+}
+
+
+fn expect_factor(tokens: &mut SplitWhitespace) -> Result<u32, String> {
+    match next_token(tokens)? {
+        "factor" => next_number::<u32>(tokens),
+        token => Err(format!("Expected factor, found {}", token)),
+    }
+}
+
+
+fn parse_certificate(tokens: &mut SplitWhitespace) -> Result<PrimalityCertificate, String> {
+    match next_token(tokens)? {
+        "small" => Ok(PrimalityCertificate::Small(next_integer(tokens)?)),
+        "pratt" => {
+            let n = next_integer(tokens)?;
+            let witness = next_integer(tokens)?;
+            let count = next_number::<usize>(tokens)?;
+            let mut factors = Vec::with_capacity(count);
+            for _ in 0..count {
+                let exponent = expect_factor(tokens)?;
+                factors.push((parse_certificate(tokens)?, exponent));
+            }
+            Ok(PrimalityCertificate::Pratt { n, witness, factors })
+        },
+        "pocklington" => {
+            let n = next_integer(tokens)?;
+            let count = next_number::<usize>(tokens)?;
+            let mut factors = Vec::with_capacity(count);
+            for _ in 0..count {
+                let exponent = expect_factor(tokens)?;
+                let witness = next_integer(tokens)?;
+                factors.push((parse_certificate(tokens)?, exponent, witness));
+            }
+            Ok(PrimalityCertificate::Pocklington { n, factors })
+        },
+        "morrison" => {
+            let n = next_integer(tokens)?;
+            let p = next_integer(tokens)?;
+            let q = next_integer(tokens)?;
+            let count = next_number::<usize>(tokens)?;
+            let mut factors = Vec::with_capacity(count);
+            for _ in 0..count {
+                let exponent = expect_factor(tokens)?;
+                factors.push((parse_certificate(tokens)?, exponent));
+            }
+            Ok(PrimalityCertificate::Morrison { n, p, q, factors })
+        },
+        token => Err(format!("Unknown certificate type {}", token)),
+    }
+}
+
+
+impl FromStr for PrimalityCertificate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s.split_whitespace();
+        let certificate = parse_certificate(&mut tokens)?;
+        match tokens.next() {
+            None => Ok(certificate),
+            Some(token) => Err(format!("Trailing token {} after certificate", token)),
+        }
+    }
+}
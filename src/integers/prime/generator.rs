@@ -0,0 +1,234 @@
+use rug::rand::RandState;
+use rug::{Complete, Integer};
+use crate::random::{rand_state_from_entropy, rand_state_from_seed, random_below, random_odd_bits, random_range};
+use super::{approx_width_in_random_interval_search, baillie_psw_is_prime, fermat_is_prime_with_rng, has_factor_in_table, rabin_miller_is_prime_with_rng, sieve_progression};
+use super::table::{small_primes, PrimeTable};
+
+
+// Default bound for trial division and sieving, as used by next_prime.
+const DEFAULT_SIEVE_BOUND: usize = 1 << 12;
+// Probability of a prime in an interval of the default width.
+const DEFAULT_INTERVAL_PROBABILITY: f64 = 0.95;
+
+
+// How candidates with the wanted number of bits are produced before the primality test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    // Independent random odd candidates, each tested directly.
+    Random,
+    // Independent random odd candidates, trial divided by the primes up to the sieve bound first.
+    TrialDivision,
+    // Consecutive numbers from a random start, trial divided one at a time.
+    Interval,
+    // An interval from a random start sieved at once, with the survivors tested in random order.
+    Sieving,
+}
+
+
+// The test a candidate has to pass to be returned as a prime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimalityTest {
+    Fermat(usize),
+    RabinMiller(usize),
+    BailliePsw,
+    // Baillie-PSW followed by t Rabin-Miller rounds, as in find_prime_with_bit_length.
+    BailliePswWithRabinMiller(usize),
+}
+
+
+// Counts over all primes made by a generator. Every candidate is either sieved out, i.e. removed
+// by trial division or sieving, or tested, except for the untested survivors left in an interval
+// once a prime has been found in it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeneratorStatistics {
+    pub primes: usize,
+    pub candidates: usize,
+    pub sieved_out: usize,
+    pub tested: usize,
+    pub intervals: usize,
+}
+
+
+// Collects the choices of the find_prime_with_bit_length functions in one place:
+//      let mut generator = PrimeGenerator::new(1024).with_strategy(Strategy::Sieving).with_seed(&seed);
+//      let p = generator.generate();
+// Every random choice, including the bases of the primality test, comes from the generator's
+// random state, so a seeded generator is reproducible.
+pub struct PrimeGenerator {
+    bits: usize,
+    strategy: Strategy,
+    sieve_bound: usize,
+    interval_width: usize,
+    test: PrimalityTest,
+    table: &'static PrimeTable,
+    rng: RandState<'static>,
+    statistics: GeneratorStatistics,
+}
+
+
+impl PrimeGenerator {
+    pub fn new(bits: usize) -> PrimeGenerator {
+        assert!(bits >= 2, "There are no primes with fewer than 2 bits");
+        PrimeGenerator {
+            bits,
+            strategy: Strategy::Sieving,
+            sieve_bound: DEFAULT_SIEVE_BOUND,
+            interval_width: approx_width_in_random_interval_search(bits, DEFAULT_INTERVAL_PROBABILITY).max(1),
+            test: PrimalityTest::BailliePsw,
+            table: small_primes(),
+            rng: rand_state_from_entropy(),
+            statistics: GeneratorStatistics::default(),
+        }
+    }
+
+    pub fn with_strategy(mut self, strategy: Strategy) -> PrimeGenerator {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn with_sieve_bound(mut self, bound: usize) -> PrimeGenerator {
+        self.sieve_bound = bound;
+        self
+    }
+
+    pub fn with_interval_width(mut self, width: usize) -> PrimeGenerator {
+        assert!(width > 0, "The interval must not be empty");
+        self.interval_width = width;
+        self
+    }
+
+    pub fn with_primality_test(mut self, test: PrimalityTest) -> PrimeGenerator {
+        self.test = test;
+        self
+    }
+
+    pub fn with_rng(mut self, rng: RandState<'static>) -> PrimeGenerator {
+        self.rng = rng;
+        self
+    }
+
+    pub fn with_seed(self, seed: &Integer) -> PrimeGenerator {
+        self.with_rng(rand_state_from_seed(seed))
+    }
+
+    pub fn get_bits(&self) -> usize {
+        self.bits
+    }
+
+    pub fn get_strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    pub fn get_statistics(&self) -> &GeneratorStatistics {
+        &self.statistics
+    }
+
+    pub fn reset_statistics(&mut self) {
+        self.statistics = GeneratorStatistics::default();
+    }
+
+
+    pub fn generate(&mut self) -> Integer {
+        let p = match self.strategy {
+            Strategy::Random => self.generate_random(false),
+            Strategy::TrialDivision => self.generate_random(true),
+            Strategy::Interval => self.generate_interval(false),
+            Strategy::Sieving => self.generate_interval(true),
+        };
+        self.statistics.primes += 1;
+        p
+    }
+
+
+    fn lower(&self) -> Integer {
+        (Integer::ONE << (self.bits as u32 - 1)).complete()
+    }
+
+
+    fn is_prime(&mut self, p: &Integer) -> bool {
+        self.statistics.tested += 1;
+        match self.test {
+            PrimalityTest::Fermat(t) => fermat_is_prime_with_rng(p, t, &mut self.rng),
+            PrimalityTest::RabinMiller(t) => rabin_miller_is_prime_with_rng(p, t, &mut self.rng),
+            PrimalityTest::BailliePsw => baillie_psw_is_prime(p),
+            PrimalityTest::BailliePswWithRabinMiller(t) => {
+                baillie_psw_is_prime(p) && (t == 0 || rabin_miller_is_prime_with_rng(p, t, &mut self.rng))
+            }
+        }
+    }
+
+
+    fn generate_random(&mut self, trial_division: bool) -> Integer {
+        loop {
+            let p: Integer = random_odd_bits(self.bits, &mut self.rng);
+            self.statistics.candidates += 1;
+            if trial_division && has_factor_in_table(&p, self.sieve_bound, self.table) {
+                self.statistics.sieved_out += 1;
+                continue;
+            }
+            if self.is_prime(&p) {
+                return p;
+            }
+        }
+    }
+
+
+    // Intervals [a, a + width) lie within the numbers of the given bits, where an interval without
+    // a prime is replaced by a new one.
+    fn generate_interval(&mut self, sieving: bool) -> Integer {
+        let lower = self.lower();
+        let width = lower.to_usize().map_or(self.interval_width, |n| n.min(self.interval_width));
+        let upper: Integer = (&lower << 1u32).complete() - width as u32 + 1u32;
+        loop {
+            let a: Integer = random_range(&lower, &upper, &mut self.rng);
+            self.statistics.intervals += 1;
+            let found = if sieving {
+                self.test_sieved_interval(&a, width)
+            } else {
+                self.test_interval(&a, width)
+            };
+            if let Some(p) = found {
+                return p;
+            }
+        }
+    }
+
+
+    fn test_interval(&mut self, a: &Integer, width: usize) -> Option<Integer> {
+        for i in 0..width {
+            let p: Integer = (a + i).into();
+            self.statistics.candidates += 1;
+            if has_factor_in_table(&p, self.sieve_bound, self.table) {
+                self.statistics.sieved_out += 1;
+                continue;
+            }
+            if self.is_prime(&p) {
+                return Some(p);
+            }
+        }
+        None
+    }
+
+
+    // Tested survivors are swapped out of the candidates, so every survivor is tested at most once.
+    fn test_sieved_interval(&mut self, a: &Integer, width: usize) -> Option<Integer> {
+        let survivors = sieve_progression(a, Integer::ONE, width, self.sieve_bound, self.table);
+        let mut candidates: Vec<usize> = survivors
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| **b)
+            .map(|(i, _)| i)
+            .collect();
+        self.statistics.candidates += width;
+        self.statistics.sieved_out += width - candidates.len();
+
+        while !candidates.is_empty() {
+            let index = random_below(&Integer::from(candidates.len()), &mut self.rng).to_usize().expect("Index is below the length");
+            let p: Integer = (a + candidates.swap_remove(index)).into();
+            if self.is_prime(&p) {
+                return Some(p);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use rug::rand::RandState;
+use rug::{Complete, Integer};
+use crate::random::rand_state_from_entropy;
+use super::{passes_primality_checks, sieve_progression, PROGRESSION_WINDOW};
+use super::table::small_primes;
+
+
+// Number of worker threads for threads = 0, falling back to one if it cannot be determined.
+fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+
+pub fn find_prime_with_bit_length_parallel(bits: usize, t: usize, bound: usize, threads: usize) -> Integer {
+    find_prime_with_bit_length_parallel_with_rng(bits, t, bound, threads, &mut rand_state_from_entropy())
+}
+
+
+// Searches windows of PROGRESSION_WINDOW odd numbers with the given number of bits on several
+// threads, or on all available cores for threads = 0. Every window is sieved by the primes up to
+// bound and its survivors are tested in increasing order.
+//
+// The windows are numbered in the order their random starts are drawn from rng, and the prime of
+// the lowest numbered window that contains one is returned. A worker only gives up a window once
+// a lower numbered one has produced a prime, so for a seeded rng the result does not depend on
+// the number of threads or on how they are scheduled.
+pub fn find_prime_with_bit_length_parallel_with_rng(bits: usize, t: usize, bound: usize, threads: usize, rng: &mut RandState) -> Integer {
+    assert!(bits >= 2, "There are no primes with fewer than 2 bits");
+    let threads = if threads == 0 { default_threads() } else { threads };
+
+    // The odd numbers of [2^(bits-1), 2^bits), of which a window takes width consecutive ones.
+    let lower: Integer = (Integer::ONE << (bits as u32 - 1)).complete() + 1u32;
+    let odd_count: Integer = (Integer::ONE << (bits as u32).saturating_sub(2)).complete();
+    let width: usize = odd_count.to_usize().map_or(PROGRESSION_WINDOW as usize, |n| n.min(PROGRESSION_WINDOW as usize));
+    let starts: Integer = odd_count - width as u32 + 1u32;
+    let two = Integer::from(2);
+    let table = small_primes();
+
+    let windows: Mutex<(usize, &mut RandState)> = Mutex::new((0, rng));
+    let best_index = AtomicUsize::new(usize::MAX);
+    let best: Mutex<Option<(usize, Integer)>> = Mutex::new(None);
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let (index, a_0) = {
+                    let mut windows = windows.lock().unwrap();
+                    let index = windows.0;
+                    windows.0 += 1;
+                    let offset: Integer = starts.random_below_ref(&mut *windows.1).complete() << 1u32;
+                    (index, offset + &lower)
+                };
+                if index > best_index.load(Ordering::Acquire) {
+                    return;
+                }
+
+                let survivors = sieve_progression(&a_0, &two, width, bound, table);
+                for (i, _) in survivors.iter().enumerate().filter(|(_, b)| **b) {
+                    if index > best_index.load(Ordering::Acquire) {
+                        return;
+                    }
+                    let p: Integer = (&a_0 + 2*i as u64).complete();
+                    if passes_primality_checks(&p, t) {
+                        let mut best = best.lock().unwrap();
+                        if best.as_ref().is_none_or(|(j, _)| index < *j) {
+                            *best = Some((index, p));
+                            best_index.fetch_min(index, Ordering::AcqRel);
+                        }
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    best.into_inner().unwrap().expect("A worker only stops after a prime is found").1
+}
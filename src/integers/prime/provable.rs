@@ -0,0 +1,191 @@
+use rug::integer::Order;
+use rug::rand::RandState;
+use rug::{ops::DivRounding, Complete, Integer};
+use sha2::{Digest, Sha256};
+use crate::random::rand_state_from_entropy;
+use crate::integers::integer_computations::pow_rug;
+use super::certificate::PrimalityCertificate;
+use super::{is_likely_prime_with_trial_division, is_prime_u64};
+
+
+// Up to this many bits a prime is proven by trial division and certified as Small.
+const SMALL_BITS: usize = 32;
+// Maurer's margin: once k bits leave at least m bits for R in n = 2Rq + 1 beyond the smallest
+// q possible, the size of q is drawn at random among those leaving m bits.
+const MAURER_MARGIN: usize = 20;
+// Trial division bound for the candidates n = 2Rq + 1, which are also checked with Baillie-PSW
+// before a witness is looked for.
+const MAURER_SIEVE_BOUND: usize = 1 << 12;
+// Output length of SHA-256 in bits.
+const HASH_BITS: usize = 256;
+
+
+// A certificate of n = 2Rq + 1 from a proven prime q with q^2 > n and a witness a satisfying
+//      a^(n-1) = 1 (mod n)    and    gcd(a^(2R) - 1, n) = 1.
+fn pocklington_step(n: &Integer, q: PrimalityCertificate, a: Integer) -> PrimalityCertificate {
+    PrimalityCertificate::Pocklington { n: n.clone(), factors: vec![(q, 1, a)] }
+}
+
+
+// A random odd prime of 2 to 32 bits, found by testing random odd numbers.
+fn random_small_prime(bits: usize, rng: &mut RandState) -> u64 {
+    let low: u64 = 1 << (bits - 1);
+    loop {
+        let n = (low + Integer::from(low).random_below(rng).to_u64().expect("below 2^32")) | 1;
+        if is_prime_u64(n) {
+            return n;
+        }
+    }
+}
+
+
+pub fn maurer_provable_prime(bits: usize) -> PrimalityCertificate {
+    maurer_provable_prime_with_rng(bits, &mut rand_state_from_entropy())
+}
+
+
+// Maurer's recursive construction of a random prime n with exactly bits bits together with its
+// proof. A proven prime q of relative size r = log q/log n is made first, where
+//      r = 2^(s-1) for s uniform in [0, 1]
+// mimics the size of the largest prime factor of a random n - 1, and then n = 2Rq + 1 is tried
+// for random R in (I, 2I] with I = floor(2^(bits-1)/(2q)). Since q^2 > n, a single witness for
+// q is a Pocklington proof of n.
+pub fn maurer_provable_prime_with_rng(bits: usize, rng: &mut RandState) -> PrimalityCertificate {
+    assert!(bits >= 2, "There are no primes with less than 2 bits");
+    if bits <= SMALL_BITS {
+        return PrimalityCertificate::Small(Integer::from(random_small_prime(bits, rng)));
+    }
+
+    // q needs at least ceil(bits/2) + 1 bits for q^2 > n.
+    let min_bits = bits.div_ceil(2) + 1;
+    let q_bits = if bits >= min_bits + MAURER_MARGIN {
+        loop {
+            let s = Integer::from(1u32 << 20).random_below(rng).to_f64()/(1u32 << 20) as f64;
+            let q_bits = ((2f64.powf(s - 1.)*bits as f64) as usize).max(min_bits);
+            if bits - q_bits >= MAURER_MARGIN {
+                break q_bits;
+            }
+        }
+    } else {
+        min_bits
+    };
+
+    let q_certificate = maurer_provable_prime_with_rng(q_bits, rng);
+    let q: Integer = q_certificate.get_prime().clone();
+    let two_q: Integer = (&q << 1u32).complete();
+    let i: Integer = (Integer::ONE << (bits as u32 - 1)).complete() / &two_q;
+
+    loop {
+        let r: Integer = &i + i.random_below_ref(rng).complete() + 1u32;
+        let n: Integer = (&r * &two_q).complete() + 1u32;
+        if !is_likely_prime_with_trial_division(&n, 0, MAURER_SIEVE_BOUND) {
+            continue;
+        }
+        let a: Integer = (&n - 3u32).complete().random_below(rng) + 2u32;
+        let n_minus_one: Integer = (&n - 1u32).complete();
+        if pow_rug(&a, &n_minus_one, &n) != 1 {
+            continue;
+        }
+        let z = pow_rug(&a, &(&r << 1u32).complete(), &n);
+        if (z - 1u32).gcd(&n) == 1 {
+            return pocklington_step(&n, q_certificate, a);
+        }
+    }
+}
+
+
+// SHA-256 of a seed, which is hashed as a big-endian string of seed_len bytes, i.e. modulo
+// 2^(8 seed_len) as FIPS 186-4 requires for seed + i.
+fn hash_seed(seed: &Integer, seed_len: usize) -> Integer {
+    let mut bytes: Vec<u8> = vec![0; seed_len];
+    let digits = seed.keep_bits_ref(8*seed_len as u32).complete().to_digits::<u8>(Order::Msf);
+    bytes[seed_len - digits.len()..].copy_from_slice(&digits);
+    Integer::from_digits(Sha256::digest(&bytes).as_slice(), Order::Msf)
+}
+
+
+// Concatenation of Hash(seed), ..., Hash(seed + iterations) with the first hash lowest, as in
+// steps 19 and 26 of FIPS 186-4 C.6.
+fn hash_blocks(seed: &Integer, seed_len: usize, iterations: usize) -> Integer {
+    let mut x: Integer = Integer::ZERO;
+    for i in 0..=iterations {
+        x += hash_seed(&(seed + i as u32).complete(), seed_len) << (i*HASH_BITS) as u32;
+    }
+    x
+}
+
+
+// The Shawe-Taylor random prime of FIPS 186-4 C.6 with SHA-256, which derives a prime of
+// exactly length bits from the input seed. Lengths up to 32 are tried as
+//      c = 2^(length-1) + (Hash(seed) xor Hash(seed + 1)) mod 2^(length-1), made odd,
+// and larger ones recursively as c = 2tc_0 + 1 for a prime c_0 of ceil(length/2) + 1 bits, which
+// is accepted with a hashed base a when z = a^(2t) satisfies gcd(z - 1, c) = 1 and
+// z^(c_0) = 1 (mod c). That is a Pocklington proof, returned with the final prime_seed and
+// prime_gen_counter so that the generation can be validated. Returns None where the standard
+// returns FAILURE.
+pub fn shawe_taylor_random_prime(length: usize, input_seed: &[u8]) -> Option<(PrimalityCertificate, Vec<u8>, usize)> {
+    let seed_len = input_seed.len();
+    assert!(seed_len > 0, "The seed must not be empty");
+    let seed = Integer::from_digits(input_seed, Order::Msf);
+    let (certificate, prime_seed, counter) = shawe_taylor_recursive(length, &seed, seed_len)?;
+
+    let mut bytes: Vec<u8> = vec![0; seed_len];
+    let digits = prime_seed.keep_bits(8*seed_len as u32).to_digits::<u8>(Order::Msf);
+    bytes[seed_len - digits.len()..].copy_from_slice(&digits);
+    Some((certificate, bytes, counter))
+}
+
+
+fn shawe_taylor_recursive(length: usize, input_seed: &Integer, seed_len: usize) -> Option<(PrimalityCertificate, Integer, usize)> {
+    if length < 2 {
+        return None;
+    }
+    if length <= SMALL_BITS {
+        let mut prime_seed: Integer = input_seed.clone();
+        let mut counter: usize = 0;
+        let top: Integer = (Integer::ONE << (length as u32 - 1)).complete();
+        loop {
+            let next: Integer = (&prime_seed + 1u32).complete();
+            let c: Integer = hash_seed(&prime_seed, seed_len) ^ hash_seed(&next, seed_len);
+            let c: Integer = (c.keep_bits(length as u32 - 1) + &top) | 1u32;
+            counter += 1;
+            prime_seed += 2u32;
+            if is_prime_u64(c.to_u64().expect("c has at most 32 bits")) {
+                return Some((PrimalityCertificate::Small(c), prime_seed, counter));
+            }
+            if counter > 4*length {
+                return None;
+            }
+        }
+    }
+
+    let (c_0_certificate, mut prime_seed, mut counter) = shawe_taylor_recursive(length.div_ceil(2) + 1, input_seed, seed_len)?;
+    let c_0: Integer = c_0_certificate.get_prime().clone();
+    let two_c_0: Integer = (&c_0 << 1u32).complete();
+    let iterations = length.div_ceil(HASH_BITS) - 1;
+    let old_counter = counter;
+
+    let top: Integer = (Integer::ONE << (length as u32 - 1)).complete();
+    let x: Integer = hash_blocks(&prime_seed, seed_len, iterations).keep_bits(length as u32 - 1) + &top;
+    prime_seed += iterations as u32 + 1;
+    let mut t: Integer = x.div_ceil(two_c_0.clone());
+
+    loop {
+        if (&t * &two_c_0).complete() + 1u32 > (&top << 1u32).complete() {
+            t = top.clone().div_ceil(two_c_0.clone());
+        }
+        let c: Integer = (&t * &two_c_0).complete() + 1u32;
+        counter += 1;
+
+        let a: Integer = hash_blocks(&prime_seed, seed_len, iterations) % (&c - 3u32).complete() + 2u32;
+        prime_seed += iterations as u32 + 1;
+        let z = pow_rug(&a, &(&t << 1u32).complete(), &c);
+        if (&z - 1u32).complete().gcd(&c) == 1 && pow_rug(&z, &c_0, &c) == 1 {
+            return Some((pocklington_step(&c, c_0_certificate, a), prime_seed, counter));
+        }
+        if counter >= 4*length + old_counter {
+            return None;
+        }
+        t += 1u32;
+    }
+}
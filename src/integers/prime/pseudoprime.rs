@@ -0,0 +1,140 @@
+use rug::{Complete, Integer};
+use crate::integers::arith::factor_with_multiplicities;
+use super::{baillie_psw_is_prime, mul_mod_u64, pow_mod_u64};
+
+
+// Single strong test to the base a for odd n > 2 that fits in a word.
+fn is_strong_probable_prime_u64(n: u64, a: u64) -> bool {
+    let s = (n - 1).trailing_zeros();
+    let r = (n - 1) >> s;
+    let mut y = pow_mod_u64(a, r, n);
+    if y == 1 || y == n - 1 {
+        return true;
+    }
+    for _ in 1..s {
+        y = mul_mod_u64(y, y, n);
+        if y == n - 1 {
+            return true;
+        }
+    }
+    false
+}
+
+
+// The bases a in [1, n - 1] with a^(n-1) = 1 (mod n). For composite n these are the bases that
+// fermat_is_prime is fooled by. Takes time linear in n, so it is meant for small n.
+pub fn fermat_liars(n: u64) -> Vec<u64> {
+    if n < 3 {
+        return Vec::new();
+    }
+    (1..n).filter(|a| pow_mod_u64(*a, n - 1, n) == 1).collect()
+}
+
+
+// The bases a in [1, n - 1] that n passes the strong test for, i.e. with n - 1 = 2^s * r,
+//      a^r = 1 (mod n)    or    a^(2^j * r) = -1 (mod n) for some j in [0, s-1].
+// For odd composite n these fool rabin_miller_is_prime and there are at most (n - 1)/4 of them.
+pub fn strong_liars(n: u64) -> Vec<u64> {
+    if n < 3 || n.is_multiple_of(2) {
+        return Vec::new();
+    }
+    (1..n).filter(|a| is_strong_probable_prime_u64(n, *a)).collect()
+}
+
+
+// Number of Fermat liars of n > 1 from its factorization,
+//      F(n) = prod over p | n of gcd(p - 1, n - 1).
+pub fn count_fermat_liars(n: &Integer) -> Integer {
+    assert!(*n > 1, "Fermat liars are only counted for n > 1");
+    let n_minus_one: Integer = (n - 1u32).complete();
+    factor_with_multiplicities(n)
+        .into_iter()
+        .map(|(p, _)| (p - 1u32).gcd(&n_minus_one))
+        .product()
+}
+
+
+// Number of strong liars of an odd composite n by Monier's formula. With n - 1 = 2^s * r,
+// p_i - 1 = 2^(s_i) * r_i for the k distinct primes p_i | n and v = min s_i,
+//      S(n) = (1 + (2^(kv) - 1)/(2^k - 1)) * prod gcd(r, r_i).
+pub fn count_strong_liars(n: &Integer) -> Integer {
+    assert!(*n > 2 && n.is_odd(), "Strong liars are only counted for odd n > 2");
+    let n_minus_one: Integer = (n - 1u32).complete();
+    let s = n_minus_one.find_one(0).expect("n - 1 is positive");
+    let r: Integer = (&n_minus_one >> s).complete();
+
+    let factors = factor_with_multiplicities(n);
+    let k = factors.len() as u32;
+    let mut v = u32::MAX;
+    let mut product: Integer = Integer::ONE.clone();
+    for (p, _) in &factors {
+        let p_minus_one: Integer = (p - 1u32).complete();
+        let s_i = p_minus_one.find_one(0).expect("p - 1 is positive");
+        v = v.min(s_i);
+        product *= (p_minus_one >> s_i).gcd(&r);
+    }
+    let sum: Integer = ((Integer::ONE << (k*v)).complete() - 1u32) / ((Integer::ONE << k).complete() - 1u32);
+    (sum + 1u32)*product
+}
+
+
+// Korselt's criterion: n is a Carmichael number, i.e. a composite n that every base coprime to n
+// is a Fermat liar for, exactly when n is squarefree with at least two prime factors and
+// p - 1 | n - 1 for every p | n. The factorization is given as pairs (p, e) of distinct primes.
+pub fn is_carmichael_with_factors(n: &Integer, factors: &[(Integer, u32)]) -> bool {
+    if factors.len() < 2 || factors.iter().any(|(_, e)| *e != 1) {
+        return false;
+    }
+    let n_minus_one: Integer = (n - 1u32).complete();
+    factors.iter().all(|(p, _)| n_minus_one.is_divisible(&(p - 1u32).complete()))
+}
+
+
+pub fn is_carmichael(n: &Integer) -> bool {
+    if *n < 3 {
+        return false;
+    }
+    is_carmichael_with_factors(n, &factor_with_multiplicities(n))
+}
+
+
+// The factors of Chernick's number with m >= 3 factors,
+//      U_m(k) = (6k + 1)(12k + 1) prod_(i = 1)^(m-2) (9 * 2^i k + 1),
+// which is a Carmichael number when all factors are prime and 2^(m-4) | k.
+fn chernick_factors(m: u32, k: &Integer) -> Vec<Integer> {
+    let mut factors: Vec<Integer> = vec![Integer::from(6)*k + 1u32, Integer::from(12)*k + 1u32];
+    for i in 1..m - 1 {
+        factors.push((Integer::from(9) << i)*k + 1u32);
+    }
+    factors
+}
+
+
+// U_m(k) with its prime factors, or None if some factor is not prime or 2^(m-4) does not divide
+// k. The factors are only probable primes, checked with Baillie-PSW.
+pub fn chernick_carmichael(m: u32, k: &Integer) -> Option<(Integer, Vec<Integer>)> {
+    assert!(m >= 3, "Chernick's construction needs at least three factors");
+    if *k < 1 || (m > 4 && !k.is_divisible_2pow(m - 4)) {
+        return None;
+    }
+    let factors = chernick_factors(m, k);
+    if !factors.iter().all(baillie_psw_is_prime) {
+        return None;
+    }
+    let n: Integer = factors.iter().product();
+    Some((n, factors))
+}
+
+
+// The Chernick Carmichael number U_m(k) for the first suitable k >= k_start.
+pub fn find_chernick_carmichael(m: u32, k_start: &Integer) -> (Integer, Vec<Integer>) {
+    assert!(m >= 3, "Chernick's construction needs at least three factors");
+    let step: Integer = (Integer::ONE << m.saturating_sub(4)).complete();
+    let mut k: Integer = (k_start.clone().max(Integer::ONE.clone()) + &step - 1u32) / &step * &step;
+    loop {
+        if let Some(carmichael) = chernick_carmichael(m, &k) {
+            return carmichael;
+        }
+        k += &step;
+    }
+}
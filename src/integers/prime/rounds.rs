@@ -0,0 +1,133 @@
+use rug::Integer;
+use crate::random::{rand_state_from_entropy, random_odd_bits};
+use super::{rabin_miller_is_prime_with_rng, PSI_13};
+
+
+// Rounds are never chosen above this, which already gives 4^(-128) for adversarial input.
+const MAX_ROUNDS: usize = 128;
+
+
+// Where the number given to Rabin-Miller comes from, which decides the error bound that holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandidateSource {
+    // Uniformly random odd numbers of the given bits, tested until one passes, as in
+    // find_prime_with_bit_length. Most composites have far fewer than n/4 strong liars, so the
+    // bounds of Damgård, Landrock and Pomerance apply.
+    Random,
+    // Numbers that may have been chosen to fool the test, for which only Rabin's bound of
+    // 4^(-t) for t rounds holds.
+    Adversarial,
+}
+
+
+// What a prime, or a number declared prime, is known to satisfy: it passed the given number of
+// Rabin-Miller rounds, and the probability that it is composite is at most 2^(error_log2).
+// Below psi_13 the test is deterministic and error_log2 is minus infinity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrimalityGuarantee {
+    pub rounds: usize,
+    pub error_log2: f64,
+}
+
+
+impl PrimalityGuarantee {
+    pub fn error_probability(&self) -> f64 {
+        self.error_log2.exp2()
+    }
+}
+
+
+// log2(2^x + 2^y + ...) without leaving the logarithms, so that tiny bounds do not underflow.
+fn log2_sum(terms: &[f64]) -> f64 {
+    let max = terms.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    max + terms.iter().map(|x| (x - max).exp2()).sum::<f64>().log2()
+}
+
+
+// log2 of the bounds of Damgård, Landrock and Pomerance on p_(k,t), the probability that a random
+// odd k-bit number passing t rounds is composite, as far as they apply to k and t:
+//      p_(k,1) < k^2 4^(2 - sqrt(k))                       for k >= 2,
+//      p_(k,t) < k^(3/2) 2^t t^(-1/2) 4^(2 - sqrt(tk))     for t = 2, k >= 88 or 3 <= t <= k/9, k >= 21,
+//      p_(k,t) < 7/20 k 2^(-5t) + 1/7 k^(15/4) 2^(-k/2 - 2t) + 12k 2^(-k/4 - 3t)
+//                                                          for t >= k/9, k >= 21,
+//      p_(k,t) < 1/7 k^(15/4) 2^(-k/2 - 2t)                for t >= k/4, k >= 21.
+fn damgard_landrock_pomerance_log2(k: f64, t: f64) -> Option<f64> {
+    let log_k = k.log2();
+    let mut bounds: Vec<f64> = Vec::new();
+    if t == 1. && k >= 2. {
+        bounds.push(2.*log_k + 2.*(2. - k.sqrt()));
+    }
+    if (t == 2. && k >= 88.) || (k >= 21. && (3. ..=k/9.).contains(&t)) {
+        bounds.push(1.5*log_k + t - 0.5*t.log2() + 2.*(2. - (t*k).sqrt()));
+    }
+    if k >= 21. {
+        if t >= k/9. {
+            bounds.push(log2_sum(&[
+                (7f64/20.).log2() + log_k - 5.*t,
+                (1f64/7.).log2() + 3.75*log_k - k/2. - 2.*t,
+                12f64.log2() + log_k - k/4. - 3.*t,
+            ]));
+        }
+        if t >= k/4. {
+            bounds.push((1f64/7.).log2() + 3.75*log_k - k/2. - 2.*t);
+        }
+    }
+    bounds.into_iter().reduce(f64::min)
+}
+
+
+// log2 of the best known bound on the error after t rounds. Additional rounds never make a
+// composite more likely to pass, so for random candidates a bound for any t' <= t holds as well.
+pub fn rabin_miller_error_log2(bits: usize, t: usize, source: CandidateSource) -> f64 {
+    match source {
+        CandidateSource::Adversarial => -2.*t as f64,
+        CandidateSource::Random => (1..=t)
+            .filter_map(|s| damgard_landrock_pomerance_log2(bits as f64, s as f64))
+            .fold(0f64, f64::min),
+    }
+}
+
+
+// The fewest rounds for which the error is at most the target probability, e.g. 2^(-100), or
+// MAX_ROUNDS if no known bound gets there. For random candidates this reproduces the table of
+// rounds in the Handbook of Applied Cryptography (Table 4.4).
+pub fn rabin_miller_rounds(bits: usize, error: f64, source: CandidateSource) -> usize {
+    assert!(error > 0. && error < 1., "The error probability must lie in (0, 1)");
+    let target = error.log2();
+    (1..=MAX_ROUNDS)
+        .find(|t| rabin_miller_error_log2(bits, *t, source) <= target)
+        .unwrap_or(MAX_ROUNDS)
+}
+
+
+// Rabin-Miller with as many rounds as an adversarially chosen n needs for the target error.
+pub fn rabin_miller_is_prime_with_error_probability(n: &Integer, error: f64) -> (bool, PrimalityGuarantee) {
+    let rounds = rabin_miller_rounds(n.significant_bits() as usize, error, CandidateSource::Adversarial);
+    let is_prime = rabin_miller_is_prime_with_rng(n, rounds, &mut rand_state_from_entropy());
+    let error_log2 = if *n < PSI_13 {
+        f64::NEG_INFINITY
+    } else {
+        rabin_miller_error_log2(n.significant_bits() as usize, rounds, CandidateSource::Adversarial)
+    };
+    (is_prime, PrimalityGuarantee { rounds, error_log2 })
+}
+
+
+// Random odd candidates tested with Rabin-Miller alone, with the rounds taken from the bounds for
+// random candidates. Trial division or sieving beforehand would change the distribution the
+// bounds are proven for, so none is done.
+pub fn find_prime_with_error_probability(bits: usize, error: f64) -> (Integer, PrimalityGuarantee) {
+    assert!(bits >= 2, "There are no primes with fewer than 2 bits");
+    let rounds = rabin_miller_rounds(bits, error, CandidateSource::Random);
+    let mut rng = rand_state_from_entropy();
+    let mut p: Integer = random_odd_bits(bits, &mut rng);
+    while !rabin_miller_is_prime_with_rng(&p, rounds, &mut rng) {
+        p = random_odd_bits(bits, &mut rng);
+    }
+    let error_log2 = if p < PSI_13 {
+        f64::NEG_INFINITY
+    } else {
+        rabin_miller_error_log2(bits, rounds, CandidateSource::Random)
+    };
+    (p, PrimalityGuarantee { rounds, error_log2 })
+}
@@ -0,0 +1,92 @@
+use std::sync::OnceLock;
+
+
+// Same bound as the small-primes file written by the generate binary.
+pub const SMALL_PRIMES_BOUND: u32 = 10_000_000;
+
+static SMALL_PRIMES: OnceLock<PrimeTable> = OnceLock::new();
+
+
+// All primes up to a limit in increasing order, so that trial division and sieving can share one
+// table instead of reading it from disk on every call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrimeTable {
+    limit: u32,
+    primes: Vec<u32>,
+}
+
+
+impl PrimeTable {
+    // Sieve of Eratosthenes over the odd numbers, where index i stands for 2i + 1 and crossing
+    // off starts at p^2.
+    pub fn sieve(limit: u32) -> PrimeTable {
+        if limit < 2 {
+            return PrimeTable { limit, primes: Vec::new() };
+        }
+        let size = (limit as usize - 1)/2 + 1;
+        let mut is_prime: Vec<bool> = vec![true; size];
+        is_prime[0] = false;
+
+        let mut i = 1;
+        while (2*i + 1)*(2*i + 1) < 2*size {
+            if is_prime[i] {
+                let p = 2*i + 1;
+                let mut j = (p*p - 1)/2;
+                while j < size {
+                    is_prime[j] = false;
+                    j += p;
+                }
+            }
+            i += 1;
+        }
+
+        let mut primes: Vec<u32> = Vec::with_capacity(size/8);
+        primes.push(2);
+        primes.extend(is_prime.iter().enumerate().filter(|(_, b)| **b).map(|(i, _)| 2*i as u32 + 1));
+        PrimeTable { limit, primes }
+    }
+
+
+    pub fn get_limit(&self) -> u32 {
+        self.limit
+    }
+
+
+    pub fn get_primes(&self) -> &[u32] {
+        &self.primes
+    }
+
+
+    // The primes p <= bound, which is all of them if bound exceeds the limit.
+    pub fn up_to(&self, bound: usize) -> &[u32] {
+        let end = self.primes.partition_point(|p| (*p as usize) <= bound);
+        &self.primes[..end]
+    }
+
+
+    pub fn largest(&self) -> Option<u32> {
+        self.primes.last().copied()
+    }
+
+
+    // Only meaningful for n up to the limit.
+    pub fn contains(&self, n: u32) -> bool {
+        self.primes.binary_search(&n).is_ok()
+    }
+
+
+    pub fn len(&self) -> usize {
+        self.primes.len()
+    }
+
+
+    pub fn is_empty(&self) -> bool {
+        self.primes.is_empty()
+    }
+}
+
+
+// Primes below SMALL_PRIMES_BOUND, sieved the first time they are needed and shared afterwards.
+pub fn small_primes() -> &'static PrimeTable {
+    SMALL_PRIMES.get_or_init(|| PrimeTable::sieve(SMALL_PRIMES_BOUND))
+}
@@ -0,0 +1,322 @@
+use crate::integers::prime::table::{PrimeTable, SMALL_PRIMES_BOUND};
+use crate::integers::sieve::{base_primes, prime_pi, sieve_segment};
+
+
+// Primes in the primorial 2*3*5*7*11*13 = 30030 that phi(x, c) is tabulated for.
+const PHI_TINY_PRIMES: usize = 6;
+const PRIMORIAL: u64 = 30030;
+const PRIMORIAL_TOTIENT: u64 = 5760;
+
+
+// pi(n) in constant time for n up to a limit, from a bitset of the primes and the number of
+// primes below every word.
+struct PiTable {
+    bits: Vec<u64>,
+    counts: Vec<u32>,
+}
+
+
+impl PiTable {
+    fn new(primes: &[u32], limit: u64) -> PiTable {
+        let words = (limit/64 + 1) as usize;
+        let mut bits: Vec<u64> = vec![0; words];
+        for &p in primes {
+            bits[p as usize/64] |= 1 << (p % 64);
+        }
+        let mut counts: Vec<u32> = Vec::with_capacity(words);
+        let mut count = 0;
+        for word in &bits {
+            counts.push(count);
+            count += word.count_ones();
+        }
+        PiTable { bits, counts }
+    }
+
+
+    fn pi(&self, n: u64) -> u64 {
+        let word = (n/64) as usize;
+        let mask: u64 = if n % 64 == 63 { u64::MAX } else { (1 << (n % 64 + 1)) - 1 };
+        (self.counts[word] + (self.bits[word] & mask).count_ones()) as u64
+    }
+}
+
+
+// A Fenwick tree over the unsieved numbers of a segment, so that the numbers left in any prefix
+// can be counted while crossing off.
+struct SegmentTree {
+    tree: Vec<i32>,
+}
+
+
+impl SegmentTree {
+    fn new(sieve: &[bool]) -> SegmentTree {
+        let mut tree: Vec<i32> = sieve.iter().map(|b| *b as i32).collect();
+        for i in 0..tree.len() {
+            let j = i | (i + 1);
+            if j < tree.len() {
+                tree[j] += tree[i];
+            }
+        }
+        SegmentTree { tree }
+    }
+
+
+    fn remove(&mut self, mut i: usize) {
+        while i < self.tree.len() {
+            self.tree[i] -= 1;
+            i |= i + 1;
+        }
+    }
+
+
+    // Number of unsieved entries in [0, i].
+    fn count(&self, i: usize) -> i64 {
+        let mut sum: i64 = 0;
+        let mut i = i as i64;
+        while i >= 0 {
+            sum += self.tree[i as usize] as i64;
+            i = (i & (i + 1)) - 1;
+        }
+        sum
+    }
+}
+
+
+fn icbrt(x: u64) -> u64 {
+    let mut r = (x as f64).cbrt() as u64;
+    while r*r*r > x {
+        r -= 1;
+    }
+    while (r + 1)*(r + 1)*(r + 1) <= x {
+        r += 1;
+    }
+    r
+}
+
+
+// phi(x, c) counts the n <= x without prime factors among the first c primes, which for the
+// primorial P of those primes is
+//      phi(x, c) = floor(x/P) phi(P) + phi(x mod P, c).
+fn phi_tiny(x: u64, table: &[u32]) -> i64 {
+    ((x/PRIMORIAL)*PRIMORIAL_TOTIENT + table[(x % PRIMORIAL) as usize] as u64) as i64
+}
+
+
+// Least prime factor and Möbius function for all n <= y, with lpf(1) taken as infinite.
+fn lpf_and_mobius(y: u64, primes: &[u64]) -> (Vec<u64>, Vec<i8>) {
+    let size = y as usize + 1;
+    let mut lpf: Vec<u64> = vec![0; size];
+    let mut mu: Vec<i8> = vec![1; size];
+    lpf[1] = u64::MAX;
+    for &p in primes.iter().skip(1).take_while(|p| **p <= y) {
+        for k in (p as usize..size).step_by(p as usize) {
+            if lpf[k] == 0 {
+                lpf[k] = p;
+            }
+            mu[k] = -mu[k];
+        }
+        let square = (p*p) as usize;
+        for k in (square..size).step_by(square) {
+            mu[k] = 0;
+        }
+    }
+    (lpf, mu)
+}
+
+
+// Counts pi(x) by the method of Lagarias, Miller and Odlyzko. With y >= x^(1/3) and a = pi(y),
+//      pi(x) = phi(x, a) + a - 1 - P2(x, a),
+// where P2(x, a) counts the n <= x with exactly two prime factors, both larger than y. Expanding
+// phi(x, a) by phi(x, b) = phi(x, b - 1) - phi(x/p_b, b - 1) leaves the ordinary leaves
+// mu(n) phi(x/n, c) for n <= y, and the special leaves -mu(m) phi(x/(p_b m), b - 1) with
+// m <= y < p_b m, which are counted by sieving the interval [1, x/y].
+pub fn prime_pi_lmo(x: u64) -> u64 {
+    if x <= SMALL_PRIMES_BOUND as u64 {
+        return prime_pi(x);
+    }
+
+    // A larger y moves work from the sieve to the special leaves.
+    let alpha = ((x as f64).log10() - 6.).max(1.);
+    let y = ((alpha*icbrt(x) as f64) as u64).min(x.isqrt());
+    let limit = x.isqrt().max(y);
+    let table = PrimeTable::sieve(limit as u32);
+    let pi_table = PiTable::new(table.get_primes(), limit);
+
+    // 1-based, so that p_b = primes[b].
+    let mut primes: Vec<u64> = vec![0];
+    primes.extend(table.get_primes().iter().map(|p| *p as u64));
+    let pi_y = pi_table.pi(y) as usize;
+    let c = PHI_TINY_PRIMES;
+
+    let phi_table: Vec<u32> = {
+        let mut count = 0;
+        (0..PRIMORIAL)
+            .map(|r| {
+                if r > 0 && primes[1..=c].iter().all(|p| r % p != 0) {
+                    count += 1;
+                }
+                count
+            })
+            .collect()
+    };
+    let (lpf, mu) = lpf_and_mobius(y, &primes);
+
+    let s1: i64 = (1..=y)
+        .filter(|n| mu[*n as usize] != 0 && lpf[*n as usize] > primes[c])
+        .map(|n| mu[n as usize] as i64 * phi_tiny(x/n, &phi_table))
+        .sum();
+
+    // Above both x^(1/4) and sqrt(y) every m is a prime larger than p_b and x/(p_b m) < p_b^2,
+    // so that phi(x/(p_b m), b - 1) follows from pi alone.
+    let easy_bound = x.isqrt().isqrt().max(y.isqrt());
+    let hard_end = (pi_table.pi(easy_bound) as usize).clamp(c, pi_y - 1);
+    let s2 = hard_special_leaves(x, y, c, hard_end, &primes, &lpf, &mu) + easy_special_leaves(x, y, hard_end, pi_y, &primes, &pi_table);
+
+    let phi = s1 + s2;
+    (phi + pi_y as i64 - 1 - p2(x, y, &primes, &pi_table)) as u64
+}
+
+
+// Leaves with b <= hard_end, counted while sieving [1, x/y] segment by segment. When p_b is
+// crossed off, phi[b] holds the numbers below the segment not divisible by p_1, ..., p_(b-1).
+fn hard_special_leaves(x: u64, y: u64, c: usize, hard_end: usize, primes: &[u64], lpf: &[u64], mu: &[i8]) -> i64 {
+    let limit = x/y + 1;
+    let segment_size = limit.isqrt().next_power_of_two().max(1 << 12);
+    let mut phi: Vec<i64> = vec![0; hard_end + 1];
+    let mut next: Vec<u64> = primes[..=hard_end].to_vec();
+    let mut sum: i64 = 0;
+
+    let mut low = 1;
+    while low < limit {
+        let high = (low + segment_size).min(limit);
+        let mut sieve: Vec<bool> = vec![true; (high - low) as usize];
+
+        for b in 1..=c {
+            let mut j = next[b];
+            while j < high {
+                sieve[(j - low) as usize] = false;
+                j += primes[b];
+            }
+            next[b] = j;
+        }
+        let mut tree = SegmentTree::new(&sieve);
+
+        for b in c + 1..=hard_end {
+            let prime = primes[b];
+            let min_m = (x/(prime*high)).max(y/prime);
+            let max_m = (x/(prime*low)).min(y);
+            if prime >= max_m {
+                break;
+            }
+
+            for m in (min_m + 1..=max_m).rev() {
+                if mu[m as usize] != 0 && prime < lpf[m as usize] {
+                    let n = prime*m;
+                    let phi_xn = phi[b] + tree.count((x/n - low) as usize);
+                    sum -= mu[m as usize] as i64 * phi_xn;
+                }
+            }
+            phi[b] += tree.count((high - low - 1) as usize);
+
+            let mut j = next[b];
+            while j < high {
+                let i = (j - low) as usize;
+                if sieve[i] {
+                    sieve[i] = false;
+                    tree.remove(i);
+                }
+                j += prime;
+            }
+            next[b] = j;
+        }
+        low = high;
+    }
+    sum
+}
+
+
+// Leaves with hard_end < b < pi(y), where m runs over the primes in (max(p_b, y/p_b), y] and
+//      phi(x/(p_b m), b - 1) = pi(x/(p_b m)) - b + 2,    or 1 if x/(p_b m) < p_b.
+// Consecutive m with the same value of pi(x/(p_b m)) are counted together.
+fn easy_special_leaves(x: u64, y: u64, hard_end: usize, pi_y: usize, primes: &[u64], pi_table: &PiTable) -> i64 {
+    let mut sum: i64 = 0;
+    for b in hard_end + 1..pi_y {
+        let prime = primes[b];
+        let mut j = (pi_table.pi(prime.max(y/prime)) + 1) as usize;
+        while j <= pi_y {
+            let z = x/(prime*primes[j]);
+            if z < prime {
+                sum += (pi_y - j + 1) as i64;
+                break;
+            }
+            let k = pi_table.pi(z) as usize;
+            let last = (pi_table.pi((x/(prime*primes[k])).min(y)) as usize).max(j);
+            sum += (last - j + 1) as i64 * (k as i64 - b as i64 + 2);
+            j = last + 1;
+        }
+    }
+    sum
+}
+
+
+// P2(x, a) = sum over y < p <= sqrt(x) of pi(x/p) - pi(p) + 1. The values x/p lie in
+// [sqrt(x), x/y], so they are counted in increasing order by one sweep of a segmented sieve.
+fn p2(x: u64, y: u64, primes: &[u64], pi_table: &PiTable) -> i64 {
+    let sqrt_x = x.isqrt();
+    let a = pi_table.pi(y) as usize;
+    let b = pi_table.pi(sqrt_x) as usize;
+    if b <= a {
+        return 0;
+    }
+
+    let mut sum: i64 = 0;
+    let high = x/y + 1;
+    let base = base_primes(high);
+    let mut count = pi_table.pi(sqrt_x - 1);
+    let mut low = sqrt_x;
+    let mut i = b;
+
+    while i > a {
+        let segment_high = (low + (1 << 16)).min(high);
+        let sieve = sieve_segment(&base, low, segment_high);
+        let mut position = low;
+        while i > a && x/primes[i] < segment_high {
+            let target = x/primes[i];
+            while position <= target {
+                count += sieve[(position - low) as usize] as u64;
+                position += 1;
+            }
+            sum += count as i64 - (i as i64 - 1);
+            i -= 1;
+        }
+        count += sieve[(position - low) as usize..].iter().filter(|b| **b).count() as u64;
+        low = segment_high;
+    }
+    sum
+}
+
+
+// li(x) = integral from 0 to x of dt/ln(t), by Ramanujan's series
+//      li(x) = gamma + ln ln x + sqrt(x) sum_(n >= 1) (-1)^(n-1) (ln x)^n / (n! 2^(n-1))
+//                                        * sum_(k = 0)^(floor((n-1)/2)) 1/(2k + 1).
+pub fn logarithmic_integral(x: f64) -> f64 {
+    assert!(x > 1., "li(x) is only computed for x > 1");
+    const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+    let ln_x = x.ln();
+
+    let mut sum = 0.;
+    let mut term = 1.;
+    let mut inner = 0.;
+    for n in 1..200 {
+        term *= -ln_x/(n as f64 * 2.);
+        if (n - 1) % 2 == 0 {
+            inner += 1./(n as f64);
+        }
+        let addend = -term*inner;
+        sum += addend;
+        if addend.abs() < 1e-17*sum.abs() {
+            break;
+        }
+    }
+    EULER_GAMMA + ln_x.ln() + x.sqrt()*sum*2.
+}
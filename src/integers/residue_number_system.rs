@@ -0,0 +1,196 @@
+use std::sync::Arc;
+use rug::{Complete, Integer};
+use crate::integers::integer_computations::extended_gcd;
+
+
+// A fixed set of pairwise coprime moduli m_1, ..., m_k together with everything needed to convert
+// back from residues, so that it can be shared between all numbers represented in it.
+#[derive(Debug, Clone)]
+pub struct ResidueBasis {
+    moduli: Vec<Integer>,
+    product: Integer,
+    // (M/m_i)^(-1) mod m_i with M the product of all moduli.
+    crt_inverses: Vec<Integer>,
+    // mixed_radix_inverses[i][j] = m_i^(-1) mod m_j for i < j.
+    mixed_radix_inverses: Vec<Vec<Integer>>,
+}
+
+
+impl ResidueBasis {
+    pub fn new(moduli: Vec<Integer>) -> Option<ResidueBasis> {
+        if moduli.is_empty() || moduli.iter().any(|m| *m <= 1) {
+            return None
+        }
+
+        let mut mixed_radix_inverses: Vec<Vec<Integer>> = Vec::with_capacity(moduli.len());
+        for (i, m_i) in moduli.iter().enumerate() {
+            let mut inverses: Vec<Integer> = Vec::with_capacity(moduli.len());
+            for (j, m_j) in moduli.iter().enumerate() {
+                if j <= i {
+                    inverses.push(Integer::ZERO.clone());
+                    continue;
+                }
+                let (d, x, _) = extended_gcd(m_i, m_j);
+                if d != 1 {
+                    return None
+                }
+                inverses.push(x.modulo(m_j));
+            }
+            mixed_radix_inverses.push(inverses);
+        }
+
+        let product: Integer = moduli.iter().product();
+        let crt_inverses: Vec<Integer> = moduli
+            .iter()
+            .map(|m| {
+                let (_, x, _) = extended_gcd(&(&product / m).complete(), m);
+                x.modulo(m)
+            })
+            .collect();
+
+        Some(ResidueBasis {
+            moduli,
+            product,
+            crt_inverses,
+            mixed_radix_inverses,
+        })
+    }
+
+
+    pub fn get_moduli(&self) -> &Vec<Integer> {
+        &self.moduli
+    }
+
+
+    // Numbers in [0, M) are represented uniquely.
+    pub fn get_product(&self) -> &Integer {
+        &self.product
+    }
+
+
+    pub fn channels(&self) -> usize {
+        self.moduli.len()
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct ResidueNumber {
+    basis: Arc<ResidueBasis>,
+    residues: Vec<Integer>,
+}
+
+
+// Equal residues only mean equal numbers in the same basis.
+impl PartialEq for ResidueNumber {
+    fn eq(&self, other: &Self) -> bool {
+        (Arc::ptr_eq(&self.basis, &other.basis) || self.basis.moduli == other.basis.moduli)
+            && self.residues == other.residues
+    }
+}
+
+
+impl ResidueNumber {
+    pub fn new(basis: Arc<ResidueBasis>, n: &Integer) -> ResidueNumber {
+        let residues: Vec<Integer> = basis.moduli.iter().map(|m| n.modulo_ref(m).complete()).collect();
+        ResidueNumber { basis, residues }
+    }
+
+
+    // Expects residues[i] to already be reduced modulo the i-th modulus.
+    pub fn from_residues(basis: Arc<ResidueBasis>, residues: Vec<Integer>) -> Option<ResidueNumber> {
+        if residues.len() != basis.channels() {
+            return None
+        }
+        Some(ResidueNumber { basis, residues })
+    }
+
+
+    pub fn get_basis(&self) -> Arc<ResidueBasis> {
+        self.basis.clone()
+    }
+
+
+    pub fn get_residues(&self) -> &Vec<Integer> {
+        &self.residues
+    }
+
+
+    // Every channel is independent, so no carries are propagated between them.
+    fn channelwise<F>(&self, rhs: &ResidueNumber, op: F) -> ResidueNumber
+    where
+        F: Fn(&Integer, &Integer, &Integer) -> Integer
+    {
+        assert_eq!(self.basis.moduli, rhs.basis.moduli, "Residue numbers must share basis");
+        let residues = self.residues
+            .iter()
+            .zip(&rhs.residues)
+            .zip(&self.basis.moduli)
+            .map(|((a, b), m)| op(a, b, m))
+            .collect();
+        ResidueNumber { basis: self.basis.clone(), residues }
+    }
+
+
+    pub fn add_ref(&self, rhs: &ResidueNumber) -> ResidueNumber {
+        self.channelwise(rhs, |a, b, m| (a + b).complete() % m)
+    }
+
+
+    pub fn sub_ref(&self, rhs: &ResidueNumber) -> ResidueNumber {
+        self.channelwise(rhs, |a, b, m| (a - b).complete().modulo(m))
+    }
+
+
+    pub fn mul_ref(&self, rhs: &ResidueNumber) -> ResidueNumber {
+        self.channelwise(rhs, |a, b, m| (a * b).complete() % m)
+    }
+
+
+    // x = sum x_i (M/m_i) ((M/m_i)^(-1) mod m_i)  (mod M)
+    pub fn to_integer_crt(&self) -> Integer {
+        let basis = &self.basis;
+        let mut x: Integer = Integer::ZERO.clone();
+        for ((residue, m), inverse) in self.residues.iter().zip(&basis.moduli).zip(&basis.crt_inverses) {
+            let coefficient: Integer = (residue * inverse).complete() % m;
+            x += coefficient * (&basis.product / m).complete();
+        }
+        x % &basis.product
+    }
+
+
+    // Finds the digits of x = v_1 + v_2 m_1 + v_3 m_1 m_2 + ... with 0 <= v_i < m_i, which only
+    // needs arithmetic modulo the single moduli.
+    pub fn mixed_radix_digits(&self) -> Vec<Integer> {
+        let basis = &self.basis;
+        let mut digits: Vec<Integer> = Vec::with_capacity(basis.channels());
+        for (j, m_j) in basis.moduli.iter().enumerate() {
+            let mut v: Integer = self.residues[j].clone();
+            for (i, digit) in digits.iter().enumerate() {
+                v = ((v - digit) * &basis.mixed_radix_inverses[i][j]).modulo(m_j);
+            }
+            digits.push(v);
+        }
+        digits
+    }
+
+
+    pub fn to_integer_mixed_radix(&self) -> Integer {
+        let mut x: Integer = Integer::ZERO.clone();
+        for (digit, m) in self.mixed_radix_digits().iter().zip(&self.basis.moduli).rev() {
+            x = x * m + digit;
+        }
+        x
+    }
+
+
+    // Representative in (-M/2, M/2], for computations such as determinants that may be negative.
+    pub fn to_integer_signed(&self) -> Integer {
+        let x = self.to_integer_mixed_radix();
+        if (&x << 1u32).complete() > self.basis.product {
+            x - &self.basis.product
+        } else {
+            x
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use rug::Integer;
+use crate::integers::prime::baillie_psw_is_prime;
+use crate::integers::prime::table::{small_primes, PrimeTable};
+
+
+// Numbers sieved at a time, small enough for the segment to stay in cache.
+const SEGMENT_SIZE: u64 = 1 << 16;
+// Candidates sieved at a time by next_prime and prev_prime above the prime table.
+const WINDOW_SIZE: usize = 1 << 12;
+// Primes used to sieve such a window before the survivors are tested.
+const WINDOW_SIEVE_BOUND: usize = 1 << 12;
+
+
+// The primes needed to sieve everything below high, i.e. those up to sqrt(high - 1).
+pub(crate) fn base_primes(high: u64) -> Vec<u32> {
+    let root = high.saturating_sub(1).isqrt();
+    let table = small_primes();
+    if root <= table.get_limit() as u64 {
+        table.up_to(root as usize).to_vec()
+    } else {
+        PrimeTable::sieve(root as u32).get_primes().to_vec()
+    }
+}
+
+
+// Marks the numbers of [low, high) as prime or not, crossing off multiples of every base prime
+// p from max(p^2, first multiple >= low). The base primes must cover sqrt(high - 1).
+pub(crate) fn sieve_segment(base_primes: &[u32], low: u64, high: u64) -> Vec<bool> {
+    let mut is_prime: Vec<bool> = vec![true; (high - low) as usize];
+    for n in low..high.min(2) {
+        is_prime[(n - low) as usize] = false;
+    }
+
+    for &p in base_primes {
+        let p = p as u128;
+        if p*p >= high as u128 {
+            break;
+        }
+        let first_multiple = (low as u128).div_ceil(p)*p;
+        let mut j = first_multiple.max(p*p);
+        while j < high as u128 {
+            is_prime[(j - low as u128) as usize] = false;
+            j += p;
+        }
+    }
+    is_prime
+}
+
+
+// The primes of [a, b) in increasing order, sieved one segment at a time.
+#[derive(Debug, Clone)]
+pub struct PrimesInRange {
+    base_primes: Vec<u32>,
+    low: u64,
+    high: u64,
+    segment: Vec<u64>,
+    position: usize,
+}
+
+
+impl Iterator for PrimesInRange {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        while self.position == self.segment.len() {
+            if self.low >= self.high {
+                return None;
+            }
+            let end = self.low.saturating_add(SEGMENT_SIZE).min(self.high);
+            let low = self.low;
+            self.segment = sieve_segment(&self.base_primes, low, end)
+                .into_iter()
+                .enumerate()
+                .filter(|(_, b)| *b)
+                .map(|(i, _)| low + i as u64)
+                .collect();
+            self.position = 0;
+            self.low = end;
+        }
+        self.position += 1;
+        Some(self.segment[self.position - 1])
+    }
+}
+
+
+pub fn primes_in_range(a: u64, b: u64) -> PrimesInRange {
+    PrimesInRange {
+        base_primes: base_primes(b),
+        low: a,
+        high: b.max(a),
+        segment: Vec::new(),
+        position: 0,
+    }
+}
+
+
+// Number of primes in [a, b), counted segment by segment without storing them.
+pub fn count_primes_in_range(a: u64, b: u64) -> u64 {
+    let base_primes = base_primes(b);
+    let mut count = 0;
+    let mut low = a;
+    while low < b {
+        let high = low.saturating_add(SEGMENT_SIZE).min(b);
+        count += sieve_segment(&base_primes, low, high).into_iter().filter(|b| *b).count() as u64;
+        low = high;
+    }
+    count
+}
+
+
+// Number of primes p <= x.
+pub fn prime_pi(x: u64) -> u64 {
+    let table = small_primes();
+    if x <= table.get_limit() as u64 {
+        return table.up_to(x as usize).len() as u64;
+    }
+    let limit = table.get_limit() as u64;
+    table.len() as u64 + count_primes_in_range(limit + 1, x.saturating_add(1))
+}
+
+
+// The n-th prime, with p_1 = 2. Beyond the prime table the search is bounded by
+//      p_n < n(ln n + ln ln n)    for n >= 6,
+// and None is returned for n = 0 or if that bound does not fit in a u64.
+pub fn nth_prime(n: u64) -> Option<u64> {
+    let table = small_primes();
+    if n == 0 {
+        return None;
+    }
+    if n <= table.len() as u64 {
+        return Some(table.get_primes()[n as usize - 1] as u64);
+    }
+
+    let ln_n = (n as f64).ln();
+    let bound = (n as f64)*(ln_n + ln_n.ln());
+    if bound >= u64::MAX as f64 {
+        return None;
+    }
+    let limit = table.get_limit() as u64;
+    primes_in_range(limit + 1, bound as u64 + 1).nth((n - table.len() as u64 - 1) as usize)
+}
+
+
+// Marks the candidates in [low, low + width) that have no prime factor below WINDOW_SIEVE_BOUND.
+// Only used above the prime table, so low is larger than every prime crossed off.
+fn sieve_window(low: &Integer, width: usize) -> Vec<bool> {
+    let mut survivors: Vec<bool> = vec![true; width];
+    for &p in small_primes().up_to(WINDOW_SIEVE_BOUND) {
+        let p = p as usize;
+        let mut j = (p - low.mod_u(p as u32) as usize) % p;
+        while j < width {
+            survivors[j] = false;
+            j += p;
+        }
+    }
+    survivors
+}
+
+
+// Smallest prime larger than n. Above the prime table every window is sieved by small primes
+// and the survivors are tested with Baillie-PSW.
+pub fn next_prime(n: &Integer) -> Integer {
+    let table = small_primes();
+    if *n < 2 {
+        return Integer::from(2);
+    }
+    if *n < table.get_limit() {
+        let n = n.to_u32().expect("n is below the table limit");
+        let index = table.get_primes().partition_point(|p| *p <= n);
+        if let Some(p) = table.get_primes().get(index) {
+            return Integer::from(*p);
+        }
+    }
+
+    let mut low: Integer = n.clone() + 1u32;
+    loop {
+        let survivors = sieve_window(&low, WINDOW_SIZE);
+        for (i, _) in survivors.iter().enumerate().filter(|(_, b)| **b) {
+            let candidate: Integer = low.clone() + i as u32;
+            if baillie_psw_is_prime(&candidate) {
+                return candidate;
+            }
+        }
+        low += WINDOW_SIZE as u32;
+    }
+}
+
+
+// Largest prime smaller than n, or None if n <= 2.
+pub fn prev_prime(n: &Integer) -> Option<Integer> {
+    let table = small_primes();
+    if *n <= 2 {
+        return None;
+    }
+    if *n <= table.get_limit() as u64 + 1 {
+        let n = n.to_u32().expect("n is at most the table limit plus one");
+        let index = table.get_primes().partition_point(|p| *p < n);
+        return Some(Integer::from(table.get_primes()[index - 1]));
+    }
+
+    let mut high: Integer = n.clone();
+    loop {
+        let low: Integer = high.clone() - WINDOW_SIZE as u32;
+        let survivors = sieve_window(&low, WINDOW_SIZE);
+        for (i, _) in survivors.iter().enumerate().rev().filter(|(_, b)| **b) {
+            let candidate: Integer = low.clone() + i as u32;
+            if baillie_psw_is_prime(&candidate) {
+                return Some(candidate);
+            }
+        }
+        high = low;
+    }
+}
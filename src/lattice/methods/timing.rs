@@ -3,32 +3,46 @@ use std::time::Instant;
 use plotters::style::full_palette::{BLUE, GREEN, ORANGE};
 use plotters::prelude::*;
 use ndarray::{array, Array1, Axis};
-use rand::{thread_rng, Rng};
+use rug::{rand::RandState, Integer};
 use crate::lattice::{methods::get_length_of_vector, Lattice};
+use crate::random::{rand_state_from_entropy, random_range};
 
 use super::is_linearly_independent;
 
 pub fn generate_random_basis(dimension: usize) -> Vec<Array1<f64>> {
+    generate_random_basis_with_rng(dimension, &mut rand_state_from_entropy())
+}
+
+// The same with entries drawn from the given random state, so a seeded one gives the same basis.
+pub fn generate_random_basis_with_rng(dimension: usize, rng: &mut RandState) -> Vec<Array1<f64>> {
     let mut basis: Vec<Array1<f64>> = Vec::with_capacity(dimension);
     for _ in 0..dimension {
-        basis.push(generate_random_vector(dimension, 1.));
+        basis.push(generate_random_vector_with_rng(dimension, 1., rng));
     }
 
     while !is_linearly_independent(&basis) {
         for i in 0..dimension {
-            basis[i] = generate_random_vector(dimension, 1.);
+            basis[i] = generate_random_vector_with_rng(dimension, 1., rng);
         }
     }
     basis
 }
 
 pub fn generate_random_vector(dimension: usize, scaling: f64) -> Array1<f64> {
-    let mut rng = thread_rng();
+    generate_random_vector_with_rng(dimension, scaling, &mut rand_state_from_entropy())
+}
+
+pub fn generate_random_vector_with_rng(dimension: usize, scaling: f64, rng: &mut RandState) -> Array1<f64> {
     let mut vector: Array1<f64> = Array1::zeros(dimension);
-    vector.map_inplace(|e| {*e = scaling*(rng.gen_range(-1000..1000) as f64)});
+    vector.map_inplace(|e| {*e = scaling*random_entry(rng)});
     vector
 }
 
+// Uniform in [-1000, 1000).
+fn random_entry(rng: &mut RandState) -> f64 {
+    random_range(&Integer::from(-1000), &Integer::from(1000), rng).to_f64()
+}
+
 
 pub fn cvp_statistics(top: usize) {
     for dimension in 2..top {
@@ -67,15 +81,19 @@ pub fn cvp_statistics(top: usize) {
 
 
 pub fn increase_basis(basis: &mut Vec<Array1<f64>>) {
-    let mut rng = thread_rng();
+    increase_basis_with_rng(basis, &mut rand_state_from_entropy())
+}
+
+
+pub fn increase_basis_with_rng(basis: &mut Vec<Array1<f64>>, rng: &mut RandState) {
     for vector in basis.iter_mut() {
-        let append = rng.gen_range(-1000..1000) as f64;
+        let append = random_entry(rng);
         vector.append(Axis(0), array![append].view());
     }
     let dimension = basis[0].len();
-    basis.push(generate_random_vector(dimension, 1.));
+    basis.push(generate_random_vector_with_rng(dimension, 1., rng));
     while !is_linearly_independent(&basis) {
-        basis[dimension-1] = generate_random_vector(dimension, 1.);
+        basis[dimension-1] = generate_random_vector_with_rng(dimension, 1., rng);
     }
 }
 
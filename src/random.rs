@@ -1,33 +1,91 @@
-use rand::{thread_rng, Rng};
-use rug::Integer;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use rug::{ops::Pow, rand::{RandGen, RandState}, Complete, Integer};
+
+pub mod number_theoretic;
+pub mod secure;
+pub mod statistics;
 
 
 pub fn randint_bits(bits: usize) -> Integer {
-    let mut n = Integer::from(1);
-    let mut rng = thread_rng();
-    for _ in 1..bits {
-        n <<= 1;
-        n += rng.gen_range(0..=1);
-    }
-    n
+    random_bits_exact(bits, &mut rand_state_from_entropy())
 }
 
 
 pub fn randint_bits_odd(bits: usize) -> Integer {
-    let mut n = randint_bits(bits-1);
-    n <<= 1;
-    n += 1;
-    n
+    random_odd_bits(bits, &mut rand_state_from_entropy())
 }
 
 
+// A number with exactly the given number of decimal digits, i.e. uniform in [10^(d-1), 10^d).
 pub fn randint_digits(digits: usize) -> Integer {
-    let mut rng = thread_rng();
-    let mut n: Integer = Integer::from(rng.gen_range(1..=9));
+    assert!(digits > 0, "A number has at least one digit");
+    let low: Integer = Integer::from(10).pow(digits as u32 - 1);
+    let high: Integer = (&low * 10u32).complete();
+    random_range(&low, &high, &mut rand_state_from_entropy())
+}
+
+
+// Uniform in [0, n) for n > 0. GMP fills whole words from the state and rejects draws of the
+// bit length of n that are too large, so there is no modulo bias.
+pub fn random_below(n: &Integer, rng: &mut RandState) -> Integer {
+    assert!(*n > 0, "The bound must be positive");
+    n.random_below_ref(rng).into()
+}
+
+
+// Uniform in [a, b) for a < b.
+pub fn random_range(a: &Integer, b: &Integer, rng: &mut RandState) -> Integer {
+    assert!(a < b, "The range must not be empty");
+    random_below(&(b - a).complete(), rng) + a
+}
+
+
+// Uniform among the numbers with exactly k bits, i.e. in [2^(k-1), 2^k).
+pub fn random_bits_exact(k: usize, rng: &mut RandState) -> Integer {
+    assert!(k > 0, "A number has at least one bit");
+    let top: Integer = (Integer::ONE << (k as u32 - 1)).complete();
+    Integer::from(Integer::random_bits(k as u32 - 1, rng)) + top
+}
+
+
+// Uniform among the odd numbers with exactly k bits.
+pub fn random_odd_bits(k: usize, rng: &mut RandState) -> Integer {
+    if k == 1 {
+        return Integer::from(1);
+    }
+    (random_bits_exact(k - 1, rng) << 1u32) + 1u32
+}
+
+
+// Lets GMP draw its random bits from a generator of the rand crate.
+struct RngCoreGen<R>(R);
 
-    for _ in 0..digits {
-        n *= 10;
-        n += rng.gen_range(0..=9);
+
+impl<R: RngCore + Send + Sync> RandGen for RngCoreGen<R> {
+    fn gen(&mut self) -> u32 {
+        self.0.next_u32()
     }
-    n
+}
+
+
+// A random state for the functions of the crate that draws from any generator of the rand crate,
+// e.g. a SecureRng for key generation or one of the number theoretic generators.
+pub fn rand_state_from_rng<R: RngCore + Send + Sync + 'static>(rng: R) -> RandState<'static> {
+    RandState::new_custom_boxed(Box::new(RngCoreGen(rng)))
+}
+
+
+// RandState::new() always starts from the same state, so a default state should be seeded from
+// the operating system to not reuse the same sequence on every call. Seeding GMP's own Mersenne
+// Twister costs about as much as a Rabin-Miller round, hence the rand generator underneath.
+pub fn rand_state_from_entropy() -> RandState<'static> {
+    rand_state_from_rng(StdRng::from_entropy())
+}
+
+
+// Gives reproducible sequences for a fixed seed.
+pub fn rand_state_from_seed(seed: &Integer) -> RandState<'static> {
+    let mut rng = RandState::new();
+    rng.seed(seed);
+    rng
 }
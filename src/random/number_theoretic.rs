@@ -0,0 +1,249 @@
+use std::sync::Arc;
+use rand::{Error, RngCore};
+use rug::rand::RandState;
+use rug::{Complete, Integer};
+use crate::algebraic_structure::finite_field::MultiplicativeGroup;
+use crate::algebraic_structure::Element;
+use crate::integers::arith::primitive_root_from_factors;
+use crate::integers::integer_computations::pow_rug;
+use crate::integers::prime::{baillie_psw_is_prime, find_prime_with_factored_order_with_rng};
+use super::{rand_state_from_entropy, random_bits_exact, random_range};
+
+
+// Fills dest with the little endian bytes of successive 32-bit outputs.
+fn fill_bytes_via_next_u32<R: RngCore>(rng: &mut R, dest: &mut [u8]) {
+    for chunk in dest.chunks_mut(4) {
+        let bytes = rng.next_u32().to_le_bytes();
+        chunk.copy_from_slice(&bytes[..chunk.len()]);
+    }
+}
+
+
+// Prime p = 3 (mod 4) with exactly the given number of bits, drawn from rng.
+fn random_blum_prime(bits: usize, rng: &mut RandState) -> Integer {
+    loop {
+        let p: Integer = random_bits_exact(bits, rng) | 3u32;
+        if baillie_psw_is_prime(&p) {
+            return p;
+        }
+    }
+}
+
+
+// The generator of Blum, Blum and Shub. For a Blum integer n = pq, with p and q distinct primes
+// that are 3 (mod 4), and a seed s coprime to n,
+//      x_0 = s^2 mod n,    x_(i+1) = x_i^2 mod n,
+// and every x_i gives its j lowest bits. Predicting the output is as hard as factoring n as long
+// as j <= log2(log2(n)). As the squares have order dividing lambda(n) = lcm(p - 1, q - 1),
+//      x_i = x_0^(2^i mod lambda(n)) mod n,
+// so whoever knows p and q can jump to any position of the stream.
+#[derive(Debug, Clone)]
+pub struct BlumBlumShub {
+    n: Integer,
+    lambda: Integer,
+    x_0: Integer,
+    state: Integer,
+    bits_per_step: u32,
+    buffer: u64,
+    buffered: u32,
+}
+
+
+impl BlumBlumShub {
+    pub fn new(bits: usize) -> BlumBlumShub {
+        BlumBlumShub::new_with_rng(bits, &mut rand_state_from_entropy())
+    }
+
+
+    // A modulus of the given bits from two Blum primes of about half the bits each, and a random
+    // seed, all drawn from rng.
+    pub fn new_with_rng(bits: usize, rng: &mut RandState) -> BlumBlumShub {
+        assert!(bits >= 16, "The modulus needs at least 16 bits");
+        loop {
+            let p = random_blum_prime(bits/2, rng);
+            let q = random_blum_prime(bits - bits/2, rng);
+            let n: Integer = (&p*&q).complete();
+            if p == q || n.significant_bits() as usize != bits {
+                continue;
+            }
+            let seed = random_range(&Integer::from(2), &n, rng);
+            if let Some(generator) = BlumBlumShub::from_primes(p, q, &seed) {
+                return generator;
+            }
+        }
+    }
+
+
+    // Returns None if p and q are not distinct primes that are 3 (mod 4), or if the seed is not
+    // coprime to n or x_0 would be 1, which only repeats itself.
+    pub fn from_primes(p: Integer, q: Integer, seed: &Integer) -> Option<BlumBlumShub> {
+        if p == q || p.mod_u(4) != 3 || q.mod_u(4) != 3 || !baillie_psw_is_prime(&p) || !baillie_psw_is_prime(&q) {
+            return None;
+        }
+        let n: Integer = (&p*&q).complete();
+        if seed.gcd_ref(&n).complete() != 1 {
+            return None;
+        }
+        let x_0 = pow_rug(seed, &Integer::from(2), &n);
+        if x_0 == 1 {
+            return None;
+        }
+        let lambda: Integer = (p - 1u32).lcm(&(q - 1u32));
+        Some(BlumBlumShub {
+            n,
+            lambda,
+            state: x_0.clone(),
+            x_0,
+            bits_per_step: 1,
+            buffer: 0,
+            buffered: 0,
+        })
+    }
+
+
+    // Takes j bits from every square instead of one, at most floor(log2(log2(n))) for the
+    // security proof to apply and never more than 32.
+    pub fn with_bits_per_step(mut self, j: u32) -> BlumBlumShub {
+        assert!(j >= 1 && j <= self.max_bits_per_step(), "At most floor(log2(log2(n))) bits can be taken per step");
+        self.bits_per_step = j;
+        self
+    }
+
+
+    pub fn max_bits_per_step(&self) -> u32 {
+        (self.n.significant_bits().ilog2()).min(32)
+    }
+
+
+    pub fn get_modulus(&self) -> &Integer {
+        &self.n
+    }
+
+
+    pub fn get_bits_per_step(&self) -> u32 {
+        self.bits_per_step
+    }
+
+
+    // Moves to x_i, so that the next output comes from x_(i+1), and discards buffered bits.
+    pub fn seek(&mut self, i: &Integer) {
+        let exponent = pow_rug(&Integer::from(2), i, &self.lambda);
+        self.state = pow_rug(&self.x_0, &exponent, &self.n);
+        self.buffer = 0;
+        self.buffered = 0;
+    }
+
+
+    // The j lowest bits of the next x_i.
+    pub fn next_bits(&mut self) -> u32 {
+        self.state = pow_rug(&self.state, &Integer::from(2), &self.n);
+        (self.state.to_u64_wrapping() & ((1u64 << self.bits_per_step) - 1)) as u32
+    }
+}
+
+
+impl RngCore for BlumBlumShub {
+    fn next_u32(&mut self) -> u32 {
+        while self.buffered < 32 {
+            self.buffer |= (self.next_bits() as u64) << self.buffered;
+            self.buffered += self.bits_per_step;
+        }
+        let value = self.buffer as u32;
+        self.buffer >>= 32;
+        self.buffered -= 32;
+        value
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let low = self.next_u32() as u64;
+        ((self.next_u32() as u64) << 32) | low
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes_via_next_u32(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+
+// The generator of Blum and Micali in the multiplicative group of Z/pZ with a generator g,
+//      x_(i+1) = g^(x_i) mod p,
+// giving the bit 1 exactly when x_(i+1) < (p - 1)/2. This bit is hardcore for the discrete
+// logarithm, so predicting the output is as hard as computing discrete logarithms modulo p.
+// Every bit costs a full exponentiation, which makes it far slower than Blum-Blum-Shub.
+#[derive(Debug, Clone)]
+pub struct BlumMicali {
+    generator: Element<MultiplicativeGroup>,
+    state: Element<MultiplicativeGroup>,
+    half: Integer,
+}
+
+
+impl BlumMicali {
+    pub fn new(bits: usize) -> BlumMicali {
+        BlumMicali::new_with_rng(bits, &mut rand_state_from_entropy())
+    }
+
+
+    // A prime of the given bits with p - 1 factored, so that its primitive root is found without
+    // factoring, with both the prime and the seed drawn from rng.
+    pub fn new_with_rng(bits: usize, rng: &mut RandState) -> BlumMicali {
+        let (p, factors) = find_prime_with_factored_order_with_rng(bits, 0, 1 << 12, rng);
+        let primes: Vec<Integer> = factors.into_iter().map(|(q, _)| q).collect();
+        let g = primitive_root_from_factors(&p, &(&p - 1u32).complete(), &primes);
+        let seed = random_range(Integer::ONE, &p, rng);
+        BlumMicali::from_parameters(p, &g, &seed).expect("The parameters are valid by construction")
+    }
+
+
+    // Returns None if p is not prime or g or the seed do not lie in [1, p - 1]. That g generates
+    // the group is not checked, as it needs the factorization of p - 1.
+    pub fn from_parameters(p: Integer, g: &Integer, seed: &Integer) -> Option<BlumMicali> {
+        if p < 3 || !baillie_psw_is_prime(&p) || *g < 1 || *g >= p || *seed < 1 || *seed >= p {
+            return None;
+        }
+        let half: Integer = (&p - 1u32).complete() >> 1u32;
+        let group = Arc::new(MultiplicativeGroup::new(p));
+        Some(BlumMicali {
+            generator: Element::new(group.clone(), g.clone()),
+            state: Element::new(group, seed.clone()),
+            half,
+        })
+    }
+
+
+    pub fn get_modulus(&self) -> Integer {
+        self.generator.get_outer_structure().mod_num().clone()
+    }
+
+
+    pub fn next_bit(&mut self) -> bool {
+        self.state = self.generator.pow(self.state.get_rep());
+        *self.state.get_rep() < self.half
+    }
+}
+
+
+impl RngCore for BlumMicali {
+    fn next_u32(&mut self) -> u32 {
+        (0..32).fold(0, |value, i| value | ((self.next_bit() as u32) << i))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let low = self.next_u32() as u64;
+        ((self.next_u32() as u64) << 32) | low
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes_via_next_u32(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
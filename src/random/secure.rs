@@ -0,0 +1,82 @@
+use rand::{CryptoRng, Error, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rug::integer::Order;
+use rug::rand::RandState;
+use rug::Integer;
+use sha2::{Digest, Sha256};
+use super::rand_state_from_rng;
+
+
+// Cryptographically secure generator based on the ChaCha stream cipher with 20 rounds, meant for
+// key generation. Seeded from the operating system it is unpredictable, seeded with a fixed seed
+// it reproduces the same stream, which is useful for test vectors but no longer secret.
+#[derive(Debug, Clone)]
+pub struct SecureRng(ChaCha20Rng);
+
+
+impl SecureRng {
+    pub fn from_entropy() -> SecureRng {
+        SecureRng(ChaCha20Rng::from_entropy())
+    }
+
+
+    // The 32 byte key is the SHA-256 hash of the seed, so that seeds of any size can be used. A
+    // sign byte goes before the bytes of |seed|, which have no leading zeros, so distinct seeds
+    // hash distinct messages.
+    pub fn from_integer_seed(seed: &Integer) -> SecureRng {
+        let mut hasher = Sha256::new();
+        hasher.update([(*seed < 0) as u8]);
+        hasher.update(seed.to_digits::<u8>(Order::Msf));
+        SecureRng(ChaCha20Rng::from_seed(hasher.finalize().into()))
+    }
+
+
+    // Position in the stream in 32-bit words, which together with set_word_pos allows jumping
+    // ahead or back without generating what lies in between.
+    pub fn get_word_pos(&self) -> u128 {
+        self.0.get_word_pos()
+    }
+
+
+    pub fn set_word_pos(&mut self, word_offset: u128) {
+        self.0.set_word_pos(word_offset);
+    }
+}
+
+
+impl RngCore for SecureRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
+
+
+impl SeedableRng for SecureRng {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: [u8; 32]) -> SecureRng {
+        SecureRng(ChaCha20Rng::from_seed(seed))
+    }
+}
+
+
+impl CryptoRng for SecureRng {}
+
+
+// A random state seeded from the operating system for the _with_rng functions of the crate, e.g.
+//      let p = find_prime_with_bit_length_with_rng(1024, 0, &mut secure_rand_state());
+pub fn secure_rand_state() -> RandState<'static> {
+    rand_state_from_rng(SecureRng::from_entropy())
+}
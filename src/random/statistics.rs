@@ -0,0 +1,290 @@
+use rand::RngCore;
+use rug::rand::RandState;
+use rug::Integer;
+use super::{rand_state_from_entropy, random_bits_exact, random_odd_bits};
+
+
+// Outcome of a statistical test whose statistic follows a chi-square distribution with the given
+// degrees of freedom for truly random input. The p-value is the probability of a statistic at
+// least this large under that distribution, so a small p-value points to non-random input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestResult {
+    pub statistic: f64,
+    pub degrees_of_freedom: usize,
+    pub p_value: f64,
+}
+
+
+impl TestResult {
+    fn new(statistic: f64, degrees_of_freedom: usize) -> TestResult {
+        TestResult { statistic, degrees_of_freedom, p_value: chi_square_p_value(statistic, degrees_of_freedom) }
+    }
+
+
+    // The input is rejected at significance level alpha, e.g. 0.001, if p < alpha.
+    pub fn passes(&self, alpha: f64) -> bool {
+        self.p_value >= alpha
+    }
+}
+
+
+// ln(Gamma(x)) for x > 0 by the Lanczos approximation, accurate to about 15 digits.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9, 676.520_368_121_885_1, -1_259.139_216_722_402_8,
+        771.323_428_777_653_1, -176.615_029_162_140_6, 12.507_343_278_686_905,
+        -0.138_571_095_265_720_12, 9.984_369_578_019_572e-6, 1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // Reflection formula Gamma(x) Gamma(1 - x) = pi/sin(pi x).
+        return (std::f64::consts::PI/(std::f64::consts::PI*x).sin()).ln() - ln_gamma(1. - x);
+    }
+    let x = x - 1.;
+    let t = x + 7.5;
+    let sum = COEFFICIENTS[1..].iter().enumerate().fold(COEFFICIENTS[0], |sum, (i, c)| sum + c/(x + i as f64 + 1.));
+    0.5*(2.*std::f64::consts::PI).ln() + (x + 0.5)*t.ln() - t + sum.ln()
+}
+
+
+// Regularized upper incomplete gamma function Q(a, x) = Gamma(a, x)/Gamma(a), by its series for
+// x < a + 1 and by its continued fraction otherwise, where each converges quickly.
+fn upper_incomplete_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0. {
+        return 1.;
+    }
+    let prefactor = (a*x.ln() - x - ln_gamma(a)).exp();
+    if x < a + 1. {
+        let mut term = 1./a;
+        let mut sum = term;
+        let mut n = a;
+        while term.abs() > sum.abs()*1e-15 {
+            n += 1.;
+            term *= x/n;
+            sum += term;
+        }
+        return (1. - prefactor*sum).max(0.);
+    }
+
+    // Modified Lentz's method for the continued fraction 1/(x + 1 - a - 1(1 - a)/(x + 3 - a - ...)).
+    let tiny = 1e-300;
+    let mut b = x + 1. - a;
+    let mut c = 1./tiny;
+    let mut d = 1./b;
+    let mut h = d;
+    for i in 1..1000 {
+        let an = -(i as f64)*(i as f64 - a);
+        b += 2.;
+        d = an*d + b;
+        if d.abs() < tiny {
+            d = tiny;
+        }
+        c = b + an/c;
+        if c.abs() < tiny {
+            c = tiny;
+        }
+        d = 1./d;
+        let delta = d*c;
+        h *= delta;
+        if (delta - 1.).abs() < 1e-15 {
+            break;
+        }
+    }
+    prefactor*h
+}
+
+
+// P(X >= statistic) for X chi-square distributed with the given degrees of freedom.
+pub fn chi_square_p_value(statistic: f64, degrees_of_freedom: usize) -> f64 {
+    assert!(degrees_of_freedom > 0, "There must be at least one degree of freedom");
+    upper_incomplete_gamma(degrees_of_freedom as f64/2., statistic/2.)
+}
+
+
+// Pearson's test of observed counts against expected ones,
+//      X = sum (o_i - e_i)^2/e_i,
+// with k - 1 degrees of freedom for k categories. Every e_i should be at least 5 or so for the
+// chi-square approximation to hold.
+pub fn chi_square_test(observed: &[usize], expected: &[f64]) -> TestResult {
+    assert!(observed.len() == expected.len() && observed.len() >= 2, "Needs at least two categories with expectations");
+    let statistic = observed
+        .iter()
+        .zip(expected)
+        .map(|(o, e)| (*o as f64 - e).powi(2)/e)
+        .sum();
+    TestResult::new(statistic, observed.len() - 1)
+}
+
+
+// Observed values in [0, k) against the uniform distribution on them.
+pub fn chi_square_uniform_test(values: &[usize], k: usize) -> TestResult {
+    let mut observed: Vec<usize> = vec![0; k];
+    for value in values {
+        observed[*value] += 1;
+    }
+    chi_square_test(&observed, &vec![values.len() as f64/k as f64; k])
+}
+
+
+// The tests below follow section 5.4.4 of the Handbook of Applied Cryptography.
+
+// Frequency (monobit) test: with n_0 zeros and n_1 ones among n bits,
+//      X_1 = (n_0 - n_1)^2/n,
+// with 1 degree of freedom.
+pub fn frequency_test(bits: &[bool]) -> TestResult {
+    let ones = bits.iter().filter(|b| **b).count() as f64;
+    let zeros = bits.len() as f64 - ones;
+    TestResult::new((zeros - ones).powi(2)/bits.len() as f64, 1)
+}
+
+
+// Serial (two-bit) test on the n - 1 overlapping pairs, with n_ij the number of pairs ij,
+//      X_2 = 4/(n - 1) (n_00^2 + n_01^2 + n_10^2 + n_11^2) - 2/n (n_0^2 + n_1^2) + 1,
+// with 2 degrees of freedom.
+pub fn serial_test(bits: &[bool]) -> TestResult {
+    assert!(bits.len() >= 21, "The serial test needs at least 21 bits");
+    let n = bits.len() as f64;
+    let ones = bits.iter().filter(|b| **b).count() as f64;
+    let zeros = n - ones;
+    let mut pairs = [0f64; 4];
+    for pair in bits.windows(2) {
+        pairs[2*pair[0] as usize + pair[1] as usize] += 1.;
+    }
+    let statistic = 4./(n - 1.)*pairs.iter().map(|p| p*p).sum::<f64>() - 2./n*(zeros*zeros + ones*ones) + 1.;
+    TestResult::new(statistic, 2)
+}
+
+
+// Largest m for the poker test on n bits, for which k = floor(n/m) >= 5 * 2^m.
+pub fn poker_block_length(n: usize) -> usize {
+    (1..).take_while(|m| n/m >= 5 << m).last().unwrap_or(1)
+}
+
+
+// Poker test on the k = floor(n/m) non-overlapping blocks of m bits, with n_i the number of
+// blocks of each of the 2^m types,
+//      X_3 = 2^m/k sum n_i^2 - k,
+// with 2^m - 1 degrees of freedom.
+pub fn poker_test(bits: &[bool], m: usize) -> TestResult {
+    assert!((1..=20).contains(&m), "The block length must lie in [1, 20]");
+    let k = bits.len()/m;
+    assert!(k > 0, "There must be at least one block");
+    let mut counts: Vec<f64> = vec![0.; 1 << m];
+    for block in bits.chunks_exact(m) {
+        counts[block.iter().fold(0, |value, b| 2*value + *b as usize)] += 1.;
+    }
+    let statistic = (1 << m) as f64/k as f64*counts.iter().map(|c| c*c).sum::<f64>() - k as f64;
+    TestResult::new(statistic, (1 << m) - 1)
+}
+
+
+// Runs test: a block is a maximal run of ones and a gap a maximal run of zeros. Of length i there
+// are about e_i = (n - i + 3)/2^(i+2) of each, which is compared with the counts B_i and G_i
+// for i up to the largest k with e_k >= 5,
+//      X_4 = sum (B_i - e_i)^2/e_i + sum (G_i - e_i)^2/e_i,
+// with 2k - 2 degrees of freedom.
+pub fn runs_test(bits: &[bool]) -> TestResult {
+    let n = bits.len() as f64;
+    let expected = |i: usize| (n - i as f64 + 3.)/2f64.powi(i as i32 + 2);
+    let k = (1..).take_while(|i| expected(*i) >= 5.).last().expect("The runs test needs at least 79 bits");
+    assert!(k >= 2, "The runs test needs at least 79 bits");
+
+    let mut blocks: Vec<f64> = vec![0.; k];
+    let mut gaps: Vec<f64> = vec![0.; k];
+    for run in bits.chunk_by(|a, b| a == b) {
+        if run.len() <= k {
+            let counts = if run[0] { &mut blocks } else { &mut gaps };
+            counts[run.len() - 1] += 1.;
+        }
+    }
+    let statistic = (1..=k)
+        .map(|i| ((blocks[i - 1] - expected(i)).powi(2) + (gaps[i - 1] - expected(i)).powi(2))/expected(i))
+        .sum();
+    TestResult::new(statistic, 2*k - 2)
+}
+
+
+// The frequency, serial, poker and runs tests together, named as given.
+pub fn test_battery(bits: &[bool]) -> Vec<(&'static str, TestResult)> {
+    vec![
+        ("frequency", frequency_test(bits)),
+        ("serial", serial_test(bits)),
+        ("poker", poker_test(bits, poker_block_length(bits.len()))),
+        ("runs", runs_test(bits)),
+    ]
+}
+
+
+// Whether every test of the battery passes at significance level alpha. With several tests the
+// chance of a random sequence failing one of them is up to four times alpha.
+pub fn passes_battery(bits: &[bool], alpha: f64) -> bool {
+    test_battery(bits).iter().all(|(_, result)| result.passes(alpha))
+}
+
+
+// The n first bits of rng, taken from the least significant bit of every word upwards.
+pub fn bits_from_rng<R: RngCore>(rng: &mut R, n: usize) -> Vec<bool> {
+    let mut bits: Vec<bool> = Vec::with_capacity(n + 31);
+    while bits.len() < n {
+        let word = rng.next_u32();
+        bits.extend((0..32).map(|i| (word >> i) & 1 == 1));
+    }
+    bits.truncate(n);
+    bits
+}
+
+
+// The bits of number from bit low up to, but excluding, bit high.
+fn bits_of_integer(number: &Integer, low: u32, high: u32) -> impl Iterator<Item = bool> + '_ {
+    (low..high).map(|i| number.get_bit(i))
+}
+
+
+pub fn bits_from_randint_bits(bits: usize, n: usize) -> Vec<bool> {
+    bits_from_random_bits_exact(bits, n, &mut rand_state_from_entropy())
+}
+
+
+// At least n bits from calls to random_bits_exact(bits, rng), leaving out the top bit that is
+// always set.
+pub fn bits_from_random_bits_exact(bits: usize, n: usize, rng: &mut RandState) -> Vec<bool> {
+    assert!(bits >= 2, "Numbers of one bit carry no randomness");
+    let mut result: Vec<bool> = Vec::with_capacity(n + bits);
+    while result.len() < n {
+        result.extend(bits_of_integer(&random_bits_exact(bits, rng), 0, bits as u32 - 1));
+    }
+    result
+}
+
+
+pub fn bits_from_randint_bits_odd(bits: usize, n: usize) -> Vec<bool> {
+    bits_from_random_odd_bits(bits, n, &mut rand_state_from_entropy())
+}
+
+
+// At least n bits from calls to random_odd_bits(bits, rng), leaving out the top and the bottom
+// bit.
+pub fn bits_from_random_odd_bits(bits: usize, n: usize, rng: &mut RandState) -> Vec<bool> {
+    assert!(bits >= 3, "Odd numbers of fewer than three bits carry no randomness");
+    let mut result: Vec<bool> = Vec::with_capacity(n + bits);
+    while result.len() < n {
+        result.extend(bits_of_integer(&random_odd_bits(bits, rng), 1, bits as u32 - 1));
+    }
+    result
+}
+
+
+pub fn randint_bits_uniformity_test(bits: usize, samples: usize) -> TestResult {
+    random_bits_exact_uniformity_test(bits, samples, &mut rand_state_from_entropy())
+}
+
+
+// Chi-square test of the values of random_bits_exact(bits, rng) against the uniform distribution
+// on [2^(bits-1), 2^bits), which catches bias across whole numbers that the bit tests may miss.
+pub fn random_bits_exact_uniformity_test(bits: usize, samples: usize, rng: &mut RandState) -> TestResult {
+    assert!((2..=16).contains(&bits), "Needs between 2 and 16 bits to count every value");
+    let offset = 1usize << (bits - 1);
+    let values: Vec<usize> = (0..samples)
+        .map(|_| random_bits_exact(bits, rng).to_usize().expect("Has at most 16 bits") - offset)
+        .collect();
+    chi_square_uniform_test(&values, offset)
+}
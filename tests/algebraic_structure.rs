@@ -2,6 +2,7 @@
 mod algebraic_structure {
     use beralg::algebraic_structure::Element;
     use beralg::algebraic_structure::finite_field::{MultiplicativeGroup, FiniteField};
+    use beralg::integers::prime::certificate::{certify_prime, PrimalityCertificate};
     use rug::{Integer, Complete, rand::RandState};
     use std::sync::Arc;
 
@@ -127,4 +128,45 @@ mod algebraic_structure {
             prime.next_prime_mut();
         }
     }
+
+
+    #[test]
+    fn test_barrett_finite_field() {
+        let n: u32 = 200;
+        let mut rng = RandState::new();
+        let mut prime: Integer = Integer::from(2);
+
+        for _ in 2..n {
+            let p = prime.clone();
+            let f = Arc::new(FiniteField::new(p.clone()).unwrap());
+            let f_barrett = Arc::new(FiniteField::new(p.clone()).unwrap().with_barrett_reduction());
+            assert!(f_barrett.uses_barrett_reduction());
+
+            let a_rand = Integer::from(rng.bits(32));
+            let b_rand = Integer::from(rng.bits(32));
+            let x_rand = Integer::from(rng.bits(32));
+
+            let a = Element::new(f.clone(), a_rand.clone());
+            let b = Element::new(f.clone(), b_rand.clone());
+            let a_barrett = Element::new(f_barrett.clone(), a_rand);
+            let b_barrett = Element::new(f_barrett.clone(), b_rand);
+
+            assert_eq!(a.mul_ref(&b).get_rep(), a_barrett.mul_ref(&b_barrett).get_rep());
+            assert_eq!(a.pow(&x_rand).get_rep(), a_barrett.pow(&x_rand).get_rep());
+
+            prime.next_prime_mut();
+        }
+    }
+
+
+    #[test]
+    fn test_finite_field_from_certificate() {
+        let p: Integer = (Integer::from(1) << 127u32) - 1u32;
+        let certificate = certify_prime(&p).unwrap();
+        let f = FiniteField::from_certificate(&certificate).unwrap();
+        assert_eq!(f.get_size(), p);
+
+        let forged = PrimalityCertificate::Small(Integer::from(561));
+        assert!(FiniteField::from_certificate(&forged).is_none());
+    }
 }
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod arith {
+    use beralg::integers::arith::*;
+    use rug::{ops::Pow, Integer, rand::RandState};
+
+    fn naive_divisors(n: u64) -> Vec<u64> {
+        (1..=n).filter(|d| n % d == 0).collect()
+    }
+
+    fn naive_gcd(a: u64, b: u64) -> u64 {
+        if b == 0 { a } else { naive_gcd(b, a % b) }
+    }
+
+    fn naive_order(a: u64, n: u64) -> Option<u64> {
+        if naive_gcd(a, n) != 1 {
+            return None;
+        }
+        let mut power = a % n;
+        let mut k = 1;
+        while power != 1 % n {
+            power = power * a % n;
+            k += 1;
+        }
+        Some(k)
+    }
+
+    #[test]
+    fn test_symbols() {
+        let mut rng = RandState::new();
+        let loops = 500;
+
+        for _ in 0..loops {
+            let a: Integer = Integer::from(Integer::random_bits(100, &mut rng)) - Integer::from(Integer::random_bits(100, &mut rng));
+            let n: Integer = Integer::from(Integer::random_bits(100, &mut rng)) - Integer::from(Integer::random_bits(100, &mut rng));
+
+            assert_eq!(kronecker(&a, &n), a.kronecker(&n), "Wrong Kronecker symbol ({}/{})", &a, &n);
+            if n > 0 && n.is_odd() {
+                assert_eq!(jacobi(&a, &n), a.jacobi(&n), "Wrong Jacobi symbol ({}/{})", &a, &n);
+            }
+        }
+    }
+
+    #[test]
+    fn test_multiplicative_functions() {
+        for n in 1..100u64 {
+            let big_n = Integer::from(n);
+            let divisors_check = naive_divisors(n);
+            let units: Vec<u64> = (1..=n).filter(|a| naive_gcd(*a, n) == 1).collect();
+            let lambda_check = units.iter().map(|a| naive_order(*a, n).unwrap()).max().unwrap();
+            let square_free = (2..=n).all(|d| n % (d*d) != 0);
+            let number_of_primes = divisors_check.iter().filter(|d| **d > 1 && naive_divisors(**d).len() == 2).count();
+
+            assert_eq!(divisors(&big_n), divisors_check.iter().map(|d| Integer::from(*d)).collect::<Vec<Integer>>());
+            assert_eq!(divisor_count(&big_n), divisors_check.len());
+            assert_eq!(divisor_sum(&big_n), divisors_check.iter().sum::<u64>());
+            assert_eq!(euler_totient(&big_n), units.len(), "Wrong totient of {}", n);
+            assert_eq!(carmichael_lambda(&big_n), lambda_check, "Wrong Carmichael lambda of {}", n);
+            assert_eq!(mobius(&big_n), if !square_free { 0 } else if number_of_primes % 2 == 0 { 1 } else { -1 }, "Wrong Möbius of {}", n);
+            assert_eq!(has_primitive_root(&big_n), lambda_check == units.len() as u64, "Wrong primitive root existence for {}", n);
+
+            for a in [0, 1, 2, 3, n / 2, n - 1] {
+                assert_eq!(multiplicative_order(&Integer::from(a), &big_n), naive_order(a, n).map(Integer::from), "Wrong order of {} mod {}", a, n);
+            }
+
+            if let Some(g) = primitive_root(&big_n) {
+                assert_eq!(multiplicative_order(&g, &big_n).unwrap(), units.len(), "{} is not a primitive root mod {}", &g, n);
+            }
+        }
+    }
+
+    #[test]
+    fn test_multiplicative_functions_reject_zero() {
+        let zero = Integer::ZERO;
+        assert!(std::panic::catch_unwind(|| euler_totient(&zero)).is_err());
+        assert!(std::panic::catch_unwind(|| carmichael_lambda(&zero)).is_err());
+        assert!(std::panic::catch_unwind(|| mobius(&zero)).is_err());
+        assert!(std::panic::catch_unwind(|| divisor_count(&zero)).is_err());
+        assert!(std::panic::catch_unwind(|| divisor_sum(&zero)).is_err());
+        assert!(std::panic::catch_unwind(|| divisors(&zero)).is_err());
+        assert!(std::panic::catch_unwind(|| euler_totient(&Integer::from(-6))).is_err());
+    }
+
+    #[test]
+    fn test_factor_with_multiplicities() {
+        let n: Integer = Integer::from(2).pow(5) * Integer::from(3).pow(2) * Integer::from(1000003);
+        let factors = factor_with_multiplicities(&n);
+        assert_eq!(factors, vec![(Integer::from(2), 5), (Integer::from(3), 2), (Integer::from(1000003), 1)]);
+
+        let product = factors.iter().fold(Integer::ONE.clone(), |acc, (p, e)| acc * p.clone().pow(*e));
+        assert_eq!(product, n);
+        assert_eq!(euler_totient(&n), Integer::from(16) * 6 * 1000002);
+
+        // Both prime factors lie far beyond any trial division bound.
+        let p = Integer::from(1000000007);
+        let q = Integer::from(4294967291u64);
+        let n: Integer = Integer::from(&p * &q) * &q;
+        assert_eq!(factor_with_multiplicities(&n), vec![(p.clone(), 1), (q.clone(), 2)]);
+        assert_eq!(euler_totient(&n), (p - 1u32) * (q.clone() - 1u32) * q);
+    }
+}
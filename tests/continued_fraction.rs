@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod continued_fraction {
+    use beralg::integers::continued_fraction::*;
+    use rug::{Integer, Complete, rand::RandState};
+
+    fn to_integers(v: &[i64]) -> Vec<Integer> {
+        v.iter().map(|a| Integer::from(*a)).collect()
+    }
+
+    fn norm(d: &Integer, (x, y): &(Integer, Integer)) -> Integer {
+        x.square_ref().complete() - d * y.square_ref().complete()
+    }
+
+    #[test]
+    fn test_rational_expansion() {
+        assert_eq!(continued_fraction_rational(&Integer::from(415), &Integer::from(93)), to_integers(&[4, 2, 6, 7]));
+        assert_eq!(continued_fraction_rational(&Integer::from(-7), &Integer::from(3)), to_integers(&[-3, 1, 2]));
+
+        let mut rng = RandState::new();
+        for _ in 0..100 {
+            let p: Integer = Integer::from(Integer::random_bits(128, &mut rng)) - Integer::from(Integer::random_bits(128, &mut rng));
+            let q: Integer = Integer::from(Integer::random_bits(128, &mut rng)) + 1;
+            let quotients = continued_fraction_rational(&p, &q);
+            let (p_last, q_last) = convergents(&quotients).pop().unwrap();
+            let g = p.gcd_ref(&q).complete();
+            assert_eq!(p_last, (&p / &g).complete(), "Last convergent of {}/{} is wrong", &p, &q);
+            assert_eq!(q_last, (&q / &g).complete(), "Last convergent of {}/{} is wrong", &p, &q);
+        }
+    }
+
+    #[test]
+    fn test_quadratic_expansion() {
+        let expansion = continued_fraction_sqrt(&Integer::from(14));
+        assert_eq!(expansion.pre_period, to_integers(&[3]));
+        assert_eq!(expansion.period, to_integers(&[1, 2, 1, 6]));
+
+        let expansion = continued_fraction_sqrt(&Integer::from(16));
+        assert_eq!(expansion.pre_period, to_integers(&[4]));
+        assert!(expansion.period.is_empty());
+
+        // The golden ratio (1 + sqrt(5))/2.
+        let expansion = continued_fraction_quadratic(&Integer::from(1), &Integer::from(2), &Integer::from(5));
+        assert!(expansion.pre_period.is_empty());
+        assert_eq!(expansion.period, to_integers(&[1]));
+
+        // (1 + sqrt(3))/(-3) = -0.910...
+        let expansion = continued_fraction_quadratic(&Integer::from(1), &Integer::from(-3), &Integer::from(3));
+        assert_eq!(expansion.quotient(0), Some(&Integer::from(-1)));
+        assert_eq!(expansion.quotient(1), Some(&Integer::from(11)));
+        assert!(!expansion.period.is_empty());
+    }
+
+    #[test]
+    fn test_pell() {
+        let d = Integer::from(61);
+        assert_eq!(pell_fundamental(&d), Some((Integer::from(1766319049), Integer::from(226153980))));
+        assert_eq!(negative_pell_fundamental(&d), Some((Integer::from(29718), Integer::from(3805))));
+        assert_eq!(negative_pell_fundamental(&Integer::from(3)), None);
+        assert_eq!(pell_fundamental(&Integer::from(25)), None);
+
+        for d in 2..200 {
+            let d = Integer::from(d);
+            if d.is_perfect_square() {
+                continue;
+            }
+            for solution in pell_solutions(&d, 5) {
+                assert_eq!(norm(&d, &solution), 1, "Wrong Pell solution for d = {}", &d);
+            }
+            if let Some(solution) = negative_pell_fundamental(&d) {
+                assert_eq!(norm(&d, &solution), -1, "Wrong negative Pell solution for d = {}", &d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_generalized_pell() {
+        // x^2 - 13y^2 = 27 has the fundamental solutions (+-40, 11) and (+-53, 14) up to units.
+        let d = Integer::from(13);
+        let n = Integer::from(27);
+        let classes = generalized_pell_solutions(&d, &n, 4);
+        assert_eq!(classes.len(), 4);
+        for class in &classes {
+            for solution in class {
+                assert_eq!(norm(&d, solution), n);
+            }
+        }
+
+        let d = Integer::from(7);
+        let n = Integer::from(-3);
+        let classes = generalized_pell_solutions(&d, &n, 3);
+        assert_eq!(classes.iter().map(|class| class[0].clone()).collect::<Vec<_>>(), vec![(Integer::from(2), Integer::from(1)), (Integer::from(-2), Integer::from(1))]);
+        for class in &classes {
+            for solution in class {
+                assert_eq!(norm(&d, solution), n);
+            }
+        }
+
+        assert!(generalized_pell_fundamental(&Integer::from(3), &Integer::from(-1)).is_empty());
+    }
+}
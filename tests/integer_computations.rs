@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod integer_computations {
+    use beralg::integers::integer_computations::*;
+    use rug::{Integer, Complete, rand::RandState};
+
+    #[test]
+    fn test_barrett_reduction() {
+        let mut rng = RandState::new();
+        let loops = 200;
+
+        for _ in 0..loops {
+            let n: Integer = Integer::from(Integer::random_bits(256, &mut rng)) + 1;
+            let barrett = BarrettContext::new(n.clone()).unwrap();
+
+            let a: Integer = Integer::from(Integer::random_bits(256, &mut rng)) % &n;
+            let b: Integer = Integer::from(Integer::random_bits(256, &mut rng)) % &n;
+            let x: Integer = Integer::from(Integer::random_bits(600, &mut rng)) - Integer::from(Integer::random_bits(600, &mut rng));
+            let e: Integer = Integer::from(Integer::random_bits(64, &mut rng));
+
+            assert_eq!(barrett.mul(&a, &b), (&a * &b).complete() % &n, "Failed {} * {} mod {}", &a, &b, &n);
+            assert_eq!(barrett.reduce(&x), x.modulo_ref(&n).complete(), "Failed {} mod {}", &x, &n);
+            assert_eq!(barrett.pow(&a, &e), pow_rug(&a, &e, &n), "Failed {}^{} mod {}", &a, &e, &n);
+        }
+
+        assert!(BarrettContext::new(Integer::ZERO).is_none());
+        assert_eq!(BarrettContext::new(Integer::from(1)).unwrap().reduce(&Integer::from(3)), 0);
+    }
+
+
+    fn check_extended_gcd(a: &Integer, b: &Integer, (d, x, y): (Integer, Integer, Integer), method: &str) {
+        assert_eq!(d, a.clone().gcd(b), "Wrong gcd of {} and {} using {}", a, b, method);
+        assert_eq!((a*&x).complete() + (b*&y).complete(), d, "Wrong coefficients for {} and {} using {}", a, b, method);
+    }
+
+    #[test]
+    fn test_extended_gcd_variants() {
+        let mut rng = RandState::new();
+        let loops = 200;
+
+        for i in 0..loops {
+            let bits = 1 + (i % 20) * 50;
+            let common: Integer = Integer::from(Integer::random_bits(bits / 2 + 1, &mut rng));
+            let mut a: Integer = Integer::from(Integer::random_bits(bits, &mut rng)) * &common;
+            let mut b: Integer = Integer::from(Integer::random_bits(bits, &mut rng)) * &common;
+            if i % 3 == 0 {
+                a = -a;
+            }
+            if i % 4 == 0 {
+                b = -b;
+            }
+
+            check_extended_gcd(&a, &b, binary_extended_gcd(&a, &b), "binary");
+            check_extended_gcd(&a, &b, lehmer_extended_gcd(&a, &b), "Lehmer");
+            check_extended_gcd(&a, &b, extended_gcd(&a, &b), "dispatcher");
+            check_extended_gcd(&b, &a, extended_gcd(&b, &a), "dispatcher");
+        }
+
+        let zero = Integer::ZERO.clone();
+        let n = Integer::from(-12);
+        check_extended_gcd(&zero, &n, binary_extended_gcd(&zero, &n), "binary");
+        check_extended_gcd(&n, &zero, lehmer_extended_gcd(&n, &zero), "Lehmer");
+        check_extended_gcd(&zero, &zero, extended_gcd(&zero, &zero), "dispatcher");
+    }
+
+
+    #[test]
+    fn test_special_form_reduction() {
+        let mut rng = RandState::new();
+        for i in 1..300u32 {
+            let x: Integer = Integer::from(Integer::random_bits(3 * i, &mut rng));
+            let mersenne: Integer = (Integer::from(1) << i) - 1u32;
+            let fermat: Integer = (Integer::from(1) << i) + 1u32;
+            assert_eq!(reduce_mod_mersenne(&x, i), x.modulo_ref(&mersenne).complete(), "Failed {} mod 2^{} - 1", &x, i);
+            assert_eq!(reduce_mod_fermat(&x, i), x.modulo_ref(&fermat).complete(), "Failed {} mod 2^{} + 1", &x, i);
+        }
+        assert_eq!(reduce_mod_mersenne(&Integer::from(7), 3), 0);
+        assert_eq!(reduce_mod_fermat(&Integer::from(-1), 3), 8);
+    }
+}
@@ -1,8 +1,18 @@
 #[cfg(test)]
 mod prime {
     use std::{io::{BufRead, BufReader}, str::FromStr, fs};
-    use rug::{Integer, integer::IsPrime};
+    use rug::{Integer, Complete, integer::IsPrime, ops::Pow, rand::RandState};
     use beralg::integers::prime::*;
+    use beralg::integers::prime::aks::*;
+    use beralg::integers::prime::certificate::*;
+    use beralg::integers::prime::generator::*;
+    use beralg::integers::prime::parallel::*;
+    use beralg::integers::prime::provable::*;
+    use beralg::integers::prime::pseudoprime::*;
+    use beralg::integers::prime::rounds::*;
+    use beralg::integers::prime::table::*;
+    use beralg::integers::arith::primitive_root_from_factors;
+    use beralg::random::{rand_state_from_entropy, rand_state_from_seed};
     use rand::{thread_rng, Rng};
 
     #[test]
@@ -14,7 +24,7 @@ mod prime {
 
         for line in reader.lines() {
             let p: Integer = Integer::from_str(&line.unwrap()).unwrap();
-            assert_eq!(true, is_likely_prime_with_trial_division(&p, t, bound), "Identified {} as non-prime", &p);
+            assert_eq!(true, is_likely_prime_with_trial_division(&p, 0, bound), "Identified {} as non-prime", &p);
             assert_eq!(true, fermat_is_prime(&p, t), "Identified {} as non-prime using Fermat-test", &p);
             assert_eq!(true, rabin_miller_is_prime(&p, t), "Identified {} as non-prime using Rabin-Miller", &p);
         }
@@ -29,7 +39,7 @@ mod prime {
 
         for line in reader.lines() {
             let n: Integer = Integer::from_str(&line.unwrap()).unwrap();
-            assert_eq!(false, is_likely_prime_with_trial_division(&n, t, bound), "Identified {} as prime", &n);
+            assert_eq!(false, is_likely_prime_with_trial_division(&n, 0, bound), "Identified {} as prime", &n);
             assert_eq!(false, fermat_is_prime(&n, t), "Identified {} as prime using Fermat-test", &n);
             assert_eq!(false, rabin_miller_is_prime(&n, t), "Identified {} as prime using Rabin-Miller", &n);
         }
@@ -44,23 +54,566 @@ mod prime {
         let mut rng = thread_rng();
         for _ in 0..reps {
             let bits = rng.gen_range(5..200);
-            let p = find_prime_with_bit_length(bits, t);
+            let p = find_prime_with_bit_length(bits, 0);
             assert!(p.is_probably_prime(t as u32) != IsPrime::No, "Found {} as prime", &p);
             assert_eq!(p.significant_bits(), bits as u32);
         }
 
         for _ in 0..reps {
             let bits = rng.gen_range(5..200);
-            let trial_division = find_prime_with_bit_length_using_trial_division(bits, t, bound_td);
+            let trial_division = find_prime_with_bit_length_using_trial_division(bits, 0, bound_td);
             assert!(trial_division.is_probably_prime(t as u32) != IsPrime::No, "Found {} as prime with trial_division", &trial_division);
             assert_eq!(trial_division.significant_bits(), bits as u32);
         }
 
         for _ in 0..reps {
             let bits = rng.gen_range(5..200);
-            let sieving = find_prime_with_bit_length_using_sieving(bits, t, bound_sieving);
+            let sieving = find_prime_with_bit_length_using_sieving(bits, 0, bound_sieving);
             assert!(sieving.is_probably_prime(t as u32) != IsPrime::No, "Found {} as prime with sieving", &sieving);
             assert_eq!(sieving.significant_bits(), bits as u32);
         }
     }
+
+
+    #[test]
+    fn test_seeded_prime_generators() {
+        // Every generator reproduces its prime from equal seeds.
+        fn reproduces<T: PartialEq + std::fmt::Debug>(generate: impl Fn(&mut RandState) -> T) {
+            let seed = Integer::from(4711);
+            assert_eq!(generate(&mut rand_state_from_seed(&seed)), generate(&mut rand_state_from_seed(&seed)));
+        }
+        reproduces(|rng| find_prime_with_bit_length_using_interval_with_rng(128, 2000, 0, 100, rng));
+        reproduces(|rng| find_prime_in_interval_with_sieving_with_rng(&Integer::from(10).pow(30), 1000, 0, 100, rng));
+        reproduces(|rng| find_prime_with_bit_length_using_sieving_with_rng(160, 0, 100, rng));
+        reproduces(|rng| find_prime_with_bit_length_using_sieving_with_rng(40, 0, 0, rng));
+        reproduces(|rng| find_sophie_germain_prime_with_bit_length_with_rng(64, 0, 1000, rng));
+        reproduces(|rng| find_safe_prime_with_bit_length_with_rng(64, 0, 1000, rng));
+        reproduces(|rng| gordon_strong_prime_with_rng(128, 0, 1000, rng));
+        reproduces(|rng| find_prime_congruent_with_bit_length_with_rng(100, &Integer::from(3), &Integer::from(4), 0, 1000, rng));
+        reproduces(|rng| find_prime_congruent_with_bit_length_with_rng(20, &Integer::from(1), &Integer::from(1 << 16), 0, 1000, rng));
+        reproduces(|rng| find_prime_with_factored_order_with_rng(128, 0, 1000, rng));
+
+        let p = find_safe_prime_with_bit_length_with_rng(64, 0, 1000, &mut rand_state_from_seed(&Integer::from(1)));
+        let q = find_safe_prime_with_bit_length_with_rng(64, 0, 1000, &mut rand_state_from_seed(&Integer::from(2)));
+        assert_ne!(p, q);
+        assert!(is_safe_prime(&p) && p.significant_bits() == 64);
+    }
+
+
+    #[test]
+    fn test_primality_test_with_supplied_rng() {
+        let file = fs::File::open("non-primes").unwrap();
+        let reader = BufReader::new(file);
+        let t = 30;
+        let seed = Integer::from(2025);
+
+        let mut rng = rand_state_from_seed(&seed);
+        for line in reader.lines() {
+            let n: Integer = Integer::from_str(&line.unwrap()).unwrap();
+            assert_eq!(false, fermat_is_prime_with_rng(&n, t, &mut rng), "Identified {} as prime using Fermat-test", &n);
+            assert_eq!(false, rabin_miller_is_prime_with_rng(&n, t, &mut rng), "Identified {} as prime using Rabin-Miller", &n);
+        }
+
+        // Carmichael number 561 = 3*11*17 has both Fermat liars and witnesses, so equal seeds
+        // should agree on every single round.
+        let n = Integer::from(561);
+        let mut rng1 = rand_state_from_seed(&seed);
+        let mut rng2 = rand_state_from_seed(&seed);
+        for _ in 0..50 {
+            assert_eq!(fermat_is_prime_with_rng(&n, 1, &mut rng1), fermat_is_prime_with_rng(&n, 1, &mut rng2));
+        }
+
+        let mut rng = rand_state_from_entropy();
+        assert!(rabin_miller_is_prime_with_rng(&Integer::from(1000003), t, &mut rng));
+    }
+
+
+    #[test]
+    fn test_deterministic_rabin_miller() {
+        let loops = 20000;
+        let mut rng = thread_rng();
+
+        for _ in 0..loops {
+            let n: u64 = rng.gen();
+            let expected = Integer::from(n).is_probably_prime(30) != IsPrime::No;
+            assert_eq!(is_prime_u64(n), expected, "Wrong answer for {}", n);
+
+            let n: u128 = rng.gen_range((u64::MAX as u128)..3317044064679887385961981u128);
+            let expected = Integer::from(n).is_probably_prime(30) != IsPrime::No;
+            assert_eq!(is_prime_u128(n), expected, "Wrong answer for {}", n);
+            assert_eq!(rabin_miller_is_prime(&Integer::from(n), 0), expected, "Wrong answer for {} through dispatch", n);
+        }
+
+        for n in 0..1000u64 {
+            assert_eq!(is_prime_u64(n), Integer::from(n).is_probably_prime(30) != IsPrime::No, "Wrong answer for {}", n);
+        }
+
+        // Strong pseudoprimes to the first few prime bases.
+        for n in [2047u64, 3215031751, 2152302898747, 3474749660383, 341550071728321, 3825123056546413051] {
+            assert!(!is_prime_u64(n), "Identified strong pseudoprime {} as prime", n);
+            assert!(!is_prime_u128(n as u128), "Identified strong pseudoprime {} as prime", n);
+        }
+        assert!(!is_prime_u128(318665857834031151167461), "Identified strong pseudoprime to the first 12 primes as prime");
+
+        let p = (Integer::from(1) << 89u32) - 1u32;
+        assert!(is_prime_u128(p.to_u128().unwrap()));
+        assert!(is_prime_u128(u128::MAX - 158));
+        assert!(!is_prime_u128(u128::MAX));
+    }
+
+
+    #[test]
+    fn test_baillie_psw() {
+        let loops = 2000;
+        let mut rng = RandState::new();
+
+        for i in 0..loops {
+            let n: Integer = Integer::from(Integer::random_bits(64 + (i % 300) as u32, &mut rng));
+            let expected = n.is_probably_prime(30) != IsPrime::No;
+            assert_eq!(baillie_psw_is_prime(&n), expected, "Wrong answer for {}", &n);
+            if n.is_odd() && n > 1 {
+                assert!(is_strong_lucas_probable_prime(&n) || !expected, "Strong Lucas test rejected prime {}", &n);
+            }
+        }
+
+        // Strong Lucas pseudoprimes are caught by the base 2 test, and strong base 2 pseudoprimes
+        // by the Lucas test.
+        for n in [5459u64, 5777, 10877, 16109, 18971, 22499, 24569, 25199, 40309, 58519] {
+            let n = Integer::from(n);
+            assert!(is_strong_lucas_probable_prime(&n), "{} should be a strong Lucas pseudoprime", &n);
+            assert!(!is_strong_probable_prime(&n, &Integer::from(2)), "{} should not be a strong pseudoprime to base 2", &n);
+        }
+        for n in [2047u64, 3277, 4033, 4681, 8321, 15841, 29341, 42799, 49141, 52633] {
+            let n = Integer::from(n);
+            assert!(is_strong_probable_prime(&n, &Integer::from(2)), "{} should be a strong pseudoprime to base 2", &n);
+            assert!(!is_strong_lucas_probable_prime(&n), "{} should not be a strong Lucas pseudoprime", &n);
+        }
+
+        let mersenne: Integer = (Integer::from(1) << 521u32) - 1u32;
+        assert!(baillie_psw_is_prime(&mersenne));
+        assert!(!baillie_psw_is_prime(&(mersenne.square_ref().complete())));
+    }
+
+
+    #[test]
+    fn test_primality_certificates() {
+        let file = fs::File::open("primes").unwrap();
+        let reader = BufReader::new(file);
+        for line in reader.lines() {
+            let p: Integer = Integer::from_str(&line.unwrap()).unwrap();
+            let certificate = certify_prime(&p).unwrap();
+            assert!(certificate.verify(), "Certificate of {} does not verify", &p);
+        }
+
+        // 2^61 - 1 has smooth n - 1, and 2^127 - 1 has n + 1 a power of two.
+        let p: Integer = (Integer::from(1) << 61u32) - 1u32;
+        let certificate = pratt_certificate(&p).unwrap();
+        assert!(certificate.verify());
+        assert!(matches!(certify_prime(&p), Some(PrimalityCertificate::Pratt { .. })));
+
+        let p: Integer = (Integer::from(1) << 127u32) - 1u32;
+        let certificate = morrison_certificate(&p).unwrap();
+        assert!(certificate.verify());
+        assert_eq!(certificate.get_prime(), &p);
+
+        // Primes with smooth n - 1 beyond the range of Pratt certificates.
+        let mut m: Integer = Integer::from(1);
+        let mut q: Integer = Integer::from(2);
+        let p = loop {
+            m *= &q;
+            q.next_prime_mut();
+            let candidate: Integer = (&m * 2u32).complete() + 1u32;
+            if m.significant_bits() > 80 && candidate.is_probably_prime(30) != IsPrime::No {
+                break candidate;
+            }
+        };
+        let certificate = pocklington_certificate(&p).unwrap();
+        assert!(certificate.verify(), "Pocklington certificate of {} does not verify", &p);
+
+        let parsed: PrimalityCertificate = certificate.to_string().parse().unwrap();
+        assert_eq!(parsed, certificate);
+        assert!(parsed.verify());
+
+        let tampered: PrimalityCertificate = certificate.to_string().replacen(&p.to_string(), &(&p + 2u32).complete().to_string(), 1).parse().unwrap();
+        assert!(!tampered.verify());
+
+        assert!(certify_prime(&(Integer::from(1000003) * Integer::from(1000033))).is_none());
+        assert!(certify_prime(&Integer::from(561)).is_none());
+        assert!("pratt 7 3".parse::<PrimalityCertificate>().is_err());
+    }
+
+
+    #[test]
+    #[should_panic(expected = "n <= 31")]
+    fn test_pepin_beyond_word_size() {
+        pepin_is_prime(32);
+    }
+
+
+    #[test]
+    fn test_special_form_primality() {
+        let mersenne_exponents = [2u32, 3, 5, 7, 13, 17, 19, 31, 61, 89, 107, 127, 521, 607, 1279];
+        for p in 2..1300u32 {
+            assert_eq!(lucas_lehmer_is_prime(p), mersenne_exponents.contains(&p), "Wrong answer for 2^{} - 1", p);
+        }
+
+        for n in 0..12u32 {
+            assert_eq!(pepin_is_prime(n), n <= 4, "Wrong answer for F_{}", n);
+        }
+
+        for n in 1..40u32 {
+            for k in (1..200u32).step_by(2) {
+                let big_n: Integer = (Integer::from(k) << n) + 1u32;
+                let expected = big_n.is_probably_prime(30) != IsPrime::No;
+                if Integer::from(k).significant_bits() > n {
+                    assert_eq!(proth_is_prime(&Integer::from(k), n), None);
+                } else {
+                    assert_eq!(proth_is_prime(&Integer::from(k), n), Some(expected), "Wrong answer for {}*2^{} + 1", k, n);
+                }
+            }
+        }
+        assert_eq!(proth_is_prime(&Integer::from(4), 5), None);
+    }
+
+
+    #[test]
+    fn test_prime_table() {
+        let table = PrimeTable::sieve(100000);
+        let mut p: Integer = Integer::from(2);
+        for &q in table.get_primes() {
+            assert_eq!(p, q, "Sieve skipped or added a prime near {}", q);
+            p.next_prime_mut();
+        }
+        assert!(p > 100000);
+        assert_eq!(table.len(), 9592);
+        assert_eq!(table.up_to(100), &table.get_primes()[..25]);
+        assert_eq!(table.largest(), Some(99991));
+        assert!(table.contains(7919) && !table.contains(7917));
+        assert!(PrimeTable::sieve(1).is_empty());
+        assert_eq!(PrimeTable::sieve(2).get_primes(), &[2]);
+
+        assert_eq!(small_primes().len(), 664579);
+        assert_eq!(small_primes().up_to(100000), table.get_primes());
+
+        let a: Integer = Integer::from(Integer::u_pow_u(10, 30));
+        let p = find_prime_in_interval_with_table(&a, 2000, 0, 1000, &table).expect("There are primes in the interval");
+        assert!(p >= a && p.is_probably_prime(30) != IsPrime::No);
+        assert!(!is_likely_prime_with_table(&Integer::from(1000003u64 * 999983), 0, 1000000, small_primes()));
+    }
+
+
+    #[test]
+    fn test_safe_and_sophie_germain_primes() {
+        let sophie_germain: [u32; 15] = [2, 3, 5, 11, 23, 29, 41, 53, 83, 89, 113, 131, 173, 179, 191];
+        let mut found: Vec<u32> = Vec::new();
+        let mut a: Integer = Integer::ZERO.clone();
+        while let Some(q) = find_sophie_germain_prime_in_interval(&a, (200 - a.to_u32().unwrap()) as usize, 0, 100) {
+            found.push(q.to_u32().unwrap());
+            a = q + 1u32;
+        }
+        assert_eq!(found, sophie_germain);
+        for p in 0..400u32 {
+            assert_eq!(is_safe_prime(&Integer::from(p)), p % 2 == 1 && sophie_germain.contains(&(p/2)), "Wrong answer for {}", p);
+        }
+
+        for bits in [32, 64, 128, 256] {
+            let p = find_safe_prime_with_bit_length(bits, 0, 10000);
+            assert_eq!(p.significant_bits(), bits as u32);
+            assert!(is_safe_prime(&p), "{} is not a safe prime", &p);
+        }
+    }
+
+
+    #[test]
+    fn test_strong_primes() {
+        for bits in [64, 128, 256, 512] {
+            let (p, r, s, u) = gordon_strong_prime(bits, 0, 10000);
+            assert_eq!(p.significant_bits(), bits as u32);
+            for q in [&p, &r, &s, &u] {
+                assert!(q.is_probably_prime(30) != IsPrime::No, "{} is not prime", q);
+            }
+            assert!((&p - Integer::ONE).complete().is_divisible(&r));
+            assert!((&p + Integer::ONE).complete().is_divisible(&s));
+            assert!((&r - Integer::ONE).complete().is_divisible(&u));
+            assert!(s.significant_bits() as usize >= bits/2 - 5);
+        }
+    }
+
+
+    #[test]
+    fn test_prime_with_congruence() {
+        let cases: [(usize, u64, u64); 6] = [(16, 1, 10007), (20, 5, 12), (64, 7, 1000), (128, 1, 1 << 40), (256, 3, 4), (40, 12345, 999983)];
+        for (bits, a, m) in cases {
+            let (a, m) = (Integer::from(a), Integer::from(m));
+            match find_prime_congruent_with_bit_length(bits, &a, &m, 0, 1000) {
+                Some(p) => {
+                    assert_eq!(p.significant_bits(), bits as u32);
+                    assert_eq!(p.modulo_ref(&m).complete(), a.modulo_ref(&m).complete());
+                    assert!(p.is_probably_prime(30) != IsPrime::No, "{} is not prime", &p);
+                },
+                None => {
+                    let mut p: Integer = a.clone();
+                    while p.significant_bits() <= bits as u32 {
+                        assert!(p.significant_bits() < bits as u32 || p.is_probably_prime(30) == IsPrime::No, "Missed the prime {}", &p);
+                        p += &m;
+                    }
+                },
+            }
+        }
+        assert_eq!(find_prime_congruent_with_bit_length(64, &Integer::from(6), &Integer::from(9), 0, 1000), None);
+
+        for bits in [2, 3, 10, 64, 300] {
+            let p = find_blum_prime_with_bit_length(bits, 0, 1000);
+            assert_eq!(p.significant_bits(), bits as u32);
+            assert_eq!(p.mod_u(4), 3);
+        }
+
+        let p = find_ntt_prime_with_bit_length(64, 32, 0, 1000).expect("There are 64-bit primes 1 mod 2^32");
+        assert_eq!(p.significant_bits(), 64);
+        assert!((p - 1u32).is_divisible_2pow(32));
+        assert_eq!(find_ntt_prime_with_bit_length(16, 16, 0, 1000), None);
+    }
+
+
+    #[test]
+    fn test_prime_with_factored_order() {
+        for bits in [16, 17, 64, 256] {
+            let (p, factors) = find_prime_with_factored_order(bits, 0, 1000);
+            assert_eq!(p.significant_bits(), bits as u32);
+            assert!(p.is_probably_prime(30) != IsPrime::No, "{} is not prime", &p);
+            let mut product: Integer = Integer::ONE.clone();
+            for (q, e) in &factors {
+                assert!(q.is_probably_prime(30) != IsPrime::No, "{} is not prime", q);
+                product *= q.clone().pow(*e);
+            }
+            let p_minus_one: Integer = (&p - 1u32).complete();
+            assert_eq!(product, p_minus_one);
+
+            let primes: Vec<Integer> = factors.into_iter().map(|(q, _)| q).collect();
+            let g = primitive_root_from_factors(&p, &p_minus_one, &primes);
+            if bits <= 17 {
+                let mut order = 1;
+                let mut power: Integer = g.clone();
+                while power != 1 {
+                    power = power * &g % &p;
+                    order += 1;
+                }
+                assert_eq!(p_minus_one, order);
+            }
+        }
+    }
+
+
+    #[test]
+    fn test_provable_primes() {
+        let mut rng = rand_state_from_seed(&Integer::from(41));
+        for bits in [2, 16, 32, 33, 40, 41, 42, 43, 44, 45, 64, 256, 1024] {
+            let certificate = maurer_provable_prime_with_rng(bits, &mut rng);
+            let p = certificate.get_prime();
+            assert!(certificate.verify(), "Invalid certificate for {}", p);
+            assert_eq!(p.significant_bits(), bits as u32);
+            assert!(p.is_probably_prime(30) != IsPrime::No, "{} is not prime", p);
+            assert_eq!(PrimalityCertificate::from_str(&certificate.to_string()), Ok(certificate.clone()));
+        }
+        assert_eq!(maurer_provable_prime(512).get_prime().significant_bits(), 512);
+
+        let seed: Vec<u8> = (0..32).collect();
+        for length in [2, 17, 32, 33, 256, 1024, 2048] {
+            let (certificate, prime_seed, counter) = shawe_taylor_random_prime(length, &seed).unwrap();
+            let p = certificate.get_prime();
+            assert!(certificate.verify(), "Invalid certificate for {}", p);
+            assert_eq!(p.significant_bits(), length as u32);
+            assert!(p.is_probably_prime(30) != IsPrime::No, "{} is not prime", p);
+            assert!(counter > 0);
+            assert_eq!(prime_seed.len(), seed.len());
+            assert_ne!(prime_seed, seed);
+
+            // The construction is deterministic in the seed.
+            assert_eq!(shawe_taylor_random_prime(length, &seed), Some((certificate.clone(), prime_seed, counter)));
+            let (other, _, _) = shawe_taylor_random_prime(length, &[0xff; 32]).unwrap();
+            assert!(other.verify());
+            if length > 17 {
+                assert_ne!(other.get_prime(), p);
+            }
+        }
+        assert_eq!(shawe_taylor_random_prime(1, &seed), None);
+    }
+
+
+    #[test]
+    fn test_parallel_prime_search() {
+        for bits in [2, 3, 8, 16, 64, 512] {
+            let p = find_prime_with_bit_length_parallel(bits, 0, 1000, 4);
+            assert_eq!(p.significant_bits(), bits as u32);
+            assert!(p.is_probably_prime(30) != IsPrime::No, "{} is not prime", &p);
+        }
+        assert_eq!(find_prime_with_bit_length_parallel(128, 0, 1000, 0).significant_bits(), 128);
+
+        // Seeded searches agree for any number of threads.
+        for bits in [32, 1024] {
+            let seed = Integer::from(bits);
+            let expected = find_prime_with_bit_length_parallel_with_rng(bits, 0, 10_000, 1, &mut rand_state_from_seed(&seed));
+            assert_eq!(expected.significant_bits(), bits as u32);
+            for threads in [2, 3, 8] {
+                let p = find_prime_with_bit_length_parallel_with_rng(bits, 0, 10_000, threads, &mut rand_state_from_seed(&seed));
+                assert_eq!(p, expected, "Different prime with {} threads", threads);
+            }
+        }
+    }
+
+
+    #[test]
+    fn test_prime_generator() {
+        let strategies = [Strategy::Random, Strategy::TrialDivision, Strategy::Interval, Strategy::Sieving];
+        let tests = [PrimalityTest::Fermat(10), PrimalityTest::RabinMiller(10), PrimalityTest::BailliePsw, PrimalityTest::BailliePswWithRabinMiller(5)];
+        for strategy in strategies {
+            for test in tests {
+                for bits in [2, 3, 10, 64, 256] {
+                    let mut generator = PrimeGenerator::new(bits).with_strategy(strategy).with_primality_test(test);
+                    for _ in 0..5 {
+                        let p = generator.generate();
+                        assert_eq!(p.significant_bits(), bits as u32, "{:?} gave {}", strategy, p);
+                        assert!(p.is_probably_prime(30) != IsPrime::No, "{:?} and {:?} gave {}", strategy, test, p);
+                    }
+                    let statistics = generator.get_statistics();
+                    assert_eq!(statistics.primes, 5);
+                    assert!(statistics.tested >= 5 && statistics.sieved_out + statistics.tested <= statistics.candidates);
+                }
+            }
+        }
+
+        for strategy in strategies {
+            let seed = Integer::from(43);
+            let mut generator = PrimeGenerator::new(512).with_strategy(strategy).with_sieve_bound(2000).with_seed(&seed);
+            let mut other = PrimeGenerator::new(512).with_strategy(strategy).with_sieve_bound(2000).with_seed(&seed);
+            for _ in 0..3 {
+                assert_eq!(generator.generate(), other.generate());
+            }
+            assert_eq!(generator.get_statistics(), other.get_statistics());
+            if strategy == Strategy::Sieving || strategy == Strategy::TrialDivision {
+                assert!(generator.get_statistics().sieved_out > 0);
+            }
+            generator.reset_statistics();
+            assert_eq!(*generator.get_statistics(), GeneratorStatistics::default());
+        }
+
+        // 1151 is the only prime in [1130, 1152), so it has to be found every time.
+        for _ in 0..100 {
+            assert_eq!(find_prime_in_interval_with_sieving(&Integer::from(1130), 22, 0, 0), Some(Integer::from(1151)));
+            assert_eq!(find_prime_in_interval_with_sieving(&Integer::from(1130), 22, 0, 30), Some(Integer::from(1151)));
+        }
+        // Small primes of the table are not sieved out of an interval containing them.
+        let p = find_prime_in_interval_with_sieving(&Integer::from(2), 3, 0, 100).unwrap();
+        assert!(p == 2 || p == 3);
+        assert_eq!(find_prime_in_interval_with_sieving(&Integer::from(24), 5, 0, 100), None);
+    }
+
+
+    #[test]
+    fn test_liars_and_carmichael_numbers() {
+        assert_eq!(fermat_liars(9), vec![1, 8]);
+        assert_eq!(strong_liars(9), vec![1, 8]);
+        assert_eq!(fermat_liars(561).len(), 320);
+        assert_eq!(strong_liars(561), vec![1, 50, 101, 103, 256, 305, 458, 460, 511, 560]);
+        assert_eq!(fermat_liars(13).len(), 12);
+        assert_eq!(strong_liars(13).len(), 12);
+
+        for n in (9..2000u64).step_by(2).filter(|n| Integer::from(*n).is_probably_prime(30) == IsPrime::No) {
+            let fermat = fermat_liars(n);
+            let strong = strong_liars(n);
+            assert_eq!(count_fermat_liars(&Integer::from(n)), fermat.len(), "Wrong number of Fermat liars for {}", n);
+            assert_eq!(count_strong_liars(&Integer::from(n)), strong.len(), "Wrong number of strong liars for {}", n);
+            assert!(strong.iter().all(|a| fermat.binary_search(a).is_ok()));
+            assert!(4*strong.len() as u64 <= n - 1);
+        }
+
+        let carmichael: Vec<u64> = (3..10000u64).filter(|n| is_carmichael(&Integer::from(*n))).collect();
+        assert_eq!(carmichael, vec![561, 1105, 1729, 2465, 2821, 6601, 8911]);
+        assert!(!is_carmichael_with_factors(&Integer::from(45), &[(Integer::from(3), 2), (Integer::from(5), 1)]));
+
+        assert_eq!(chernick_carmichael(3, &Integer::from(1)).unwrap().0, 1729);
+        assert_eq!(chernick_carmichael(3, &Integer::from(2)), None);
+        assert_eq!(find_chernick_carmichael(3, &Integer::from(2)).0, 294409);
+        assert!(is_carmichael(&Integer::from(294409)));
+
+        for (m, k_start) in [(3, Integer::from(1) << 40), (4, Integer::from(1) << 20), (5, Integer::from(1000))] {
+            let (n, factors) = find_chernick_carmichael(m, &k_start);
+            assert_eq!(factors.len(), m as usize);
+            assert_eq!(factors.iter().product::<Integer>(), n);
+            let factors: Vec<(Integer, u32)> = factors.into_iter().map(|p| (p, 1)).collect();
+            assert!(is_carmichael_with_factors(&n, &factors), "{} is not a Carmichael number", n);
+            assert!(fermat_is_prime(&n, 20), "Fermat test exposed the Carmichael number {}", n);
+            assert!(!rabin_miller_is_prime(&n, 20), "Rabin-Miller test missed the Carmichael number {}", n);
+        }
+    }
+
+
+    #[test]
+    fn test_rabin_miller_rounds() {
+        // Table 4.4 of the Handbook of Applied Cryptography for an error of at most 2^(-80).
+        let table: [(usize, usize); 11] = [(150, 18), (200, 15), (250, 12), (300, 9), (350, 8), (400, 7), (450, 6), (550, 5), (650, 4), (850, 3), (1300, 2)];
+        for (bits, rounds) in table {
+            assert_eq!(rabin_miller_rounds(bits, 2f64.powi(-80), CandidateSource::Random), rounds, "Wrong rounds for {} bits", bits);
+        }
+        assert_eq!(rabin_miller_rounds(1024, 2f64.powi(-100), CandidateSource::Adversarial), 50);
+        assert_eq!(rabin_miller_rounds(1024, 2f64.powi(-101), CandidateSource::Adversarial), 51);
+        for bits in [100, 512, 2048] {
+            for t in 1..40 {
+                assert!(rabin_miller_error_log2(bits, t + 1, CandidateSource::Random) <= rabin_miller_error_log2(bits, t, CandidateSource::Random));
+            }
+        }
+
+        let (p, guarantee) = find_prime_with_error_probability(1024, 2f64.powi(-100));
+        assert_eq!(p.significant_bits(), 1024);
+        assert!(p.is_probably_prime(30) != IsPrime::No, "{} is not prime", &p);
+        assert_eq!(guarantee.rounds, 4);
+        assert!(guarantee.error_log2 <= -100. && guarantee.error_probability() <= 2f64.powi(-100));
+        let (p, guarantee) = find_prime_with_error_probability(64, 2f64.powi(-100));
+        assert!(p.is_probably_prime(30) != IsPrime::No, "{} is not prime", &p);
+        assert_eq!(guarantee.error_probability(), 0.);
+
+        let p: Integer = (Integer::from(1) << 127u32) - 1u32;
+        let (is_prime, guarantee) = rabin_miller_is_prime_with_error_probability(&p, 2f64.powi(-64));
+        assert!(is_prime);
+        assert_eq!(guarantee, PrimalityGuarantee { rounds: 32, error_log2: -64. });
+        let (is_prime, _) = rabin_miller_is_prime_with_error_probability(&(p.clone()*&p), 2f64.powi(-64));
+        assert!(!is_prime);
+    }
+
+
+    #[test]
+    fn test_aks() {
+        for n in 0..2000u64 {
+            assert_eq!(aks_is_prime(&Integer::from(n)), is_prime_u64(n), "AKS is wrong for {}", n);
+        }
+        for n in [561u64, 1105, 1729, 2465, 2821, 6601, 8911, 3*3*3*3*3*3*3, 1 << 20, 104729*104729] {
+            assert!(!aks_is_prime(&Integer::from(n)), "AKS took {} as prime", n);
+        }
+
+        let file = fs::File::open("primes").unwrap();
+        for line in BufReader::new(file).lines() {
+            let p: Integer = Integer::from_str(&line.unwrap()).unwrap();
+            assert_eq!(aks_is_prime(&p), rabin_miller_is_prime(&p, 30), "AKS and Rabin-Miller disagree on {}", &p);
+        }
+        let file = fs::File::open("non-primes").unwrap();
+        for line in BufReader::new(file).lines() {
+            let n: Integer = Integer::from_str(&line.unwrap()).unwrap();
+            assert_eq!(aks_is_prime(&n), rabin_miller_is_prime(&n, 30), "AKS and Rabin-Miller disagree on {}", &n);
+        }
+
+        // Above 2^64, where the coefficients no longer fit in a word.
+        let n: Integer = Integer::from(4294967291u64)*Integer::from(4294967279u64)*Integer::from(65521);
+        assert!(!aks_is_prime(&n));
+    }
+
+
+    // The smallest prime above 2^64, which takes the multi-word path through every congruence.
+    // It takes a quarter of an hour or so, so it only runs when asked for.
+    #[test]
+    #[ignore]
+    fn test_aks_above_word_size() {
+        let p: Integer = (Integer::from(1) << 64u32) + 13u32;
+        assert!(aks_is_prime(&p));
+        assert!(!aks_is_prime(&(p*Integer::from(3))));
+    }
 }
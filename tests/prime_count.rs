@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod prime_count {
+    use beralg::integers::prime_count::*;
+    use beralg::integers::sieve::prime_pi;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_lmo_against_sieve() {
+        let mut rng = thread_rng();
+        for _ in 0..10 {
+            let x: u64 = rng.gen_range(1..50_000_000);
+            assert_eq!(prime_pi_lmo(x), prime_pi(x), "Wrong count of primes up to {}", x);
+        }
+    }
+
+    #[test]
+    fn test_lmo_known_values() {
+        let values: [(u64, u64); 4] = [
+            (1_000_000_000, 50847534),
+            (4_294_967_296, 203280221),
+            (10_000_000_000, 455052511),
+            (100_000_000_000, 4118054813),
+        ];
+        for (x, pi) in values {
+            assert_eq!(prime_pi_lmo(x), pi, "Wrong count of primes up to {}", x);
+        }
+    }
+
+    #[test]
+    fn test_logarithmic_integral() {
+        assert!((logarithmic_integral(2.) - 1.045163780117492).abs() < 1e-12);
+        assert!((logarithmic_integral(1e6) - 78627.54915946).abs() < 1e-6);
+        let ratio = logarithmic_integral(1e11)/(prime_pi_lmo(100_000_000_000) as f64);
+        assert!((ratio - 1.).abs() < 1e-5);
+    }
+}
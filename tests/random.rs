@@ -0,0 +1,226 @@
+#[cfg(test)]
+mod random {
+    use rand::{RngCore, SeedableRng};
+    use rug::{Integer, ops::Pow};
+    use beralg::random::*;
+    use beralg::random::number_theoretic::*;
+    use beralg::random::secure::*;
+    use beralg::random::statistics::*;
+    use beralg::integers::prime::{baillie_psw_is_prime, find_prime_with_bit_length_with_rng};
+
+    #[test]
+    fn test_random_range() {
+        let mut rng = rand_state_from_entropy();
+        let a = Integer::from(-37);
+        let b = Integer::from(1) << 130;
+        for _ in 0..1000 {
+            let x = random_range(&a, &b, &mut rng);
+            assert!(a <= x && x < b);
+        }
+        for _ in 0..100 {
+            assert_eq!(random_range(&Integer::from(5), &Integer::from(6), &mut rng), 5);
+            assert!(random_below(&Integer::from(3), &mut rng) < 3);
+        }
+    }
+
+    #[test]
+    fn test_random_range_is_uniform() {
+        let mut rng = rand_state_from_seed(&Integer::from(2024));
+        let mut counts = [0usize; 10];
+        for _ in 0..100000 {
+            let x = random_range(&Integer::from(-5), &Integer::from(5), &mut rng);
+            counts[(x + 5u32).to_usize().unwrap()] += 1;
+        }
+        for count in counts {
+            assert!((9500..10500).contains(&count), "{:?}", counts);
+        }
+    }
+
+    #[test]
+    fn test_random_bits() {
+        let mut rng = rand_state_from_entropy();
+        for k in 1..200 {
+            let x = random_bits_exact(k, &mut rng);
+            assert_eq!(x.significant_bits() as usize, k);
+            let y = random_odd_bits(k, &mut rng);
+            assert_eq!(y.significant_bits() as usize, k);
+            assert!(y.is_odd());
+            assert_eq!(randint_bits(k).significant_bits() as usize, k);
+            assert!(randint_bits_odd(k).is_odd());
+        }
+
+        // The low bits of the odd numbers with three bits are 01 or 11, and both should occur.
+        let draws: Vec<Integer> = (0..100).map(|_| random_odd_bits(3, &mut rng)).collect();
+        assert!(draws.contains(&Integer::from(5)) && draws.contains(&Integer::from(7)));
+    }
+
+    #[test]
+    fn test_randint_digits() {
+        for digits in 1..50 {
+            let x = randint_digits(digits);
+            assert_eq!(x.to_string().len(), digits);
+            assert!(x >= Integer::from(10).pow(digits as u32 - 1));
+        }
+    }
+
+    #[test]
+    fn test_seeded_reproducibility() {
+        let seed = Integer::from(123456789);
+        let mut first = rand_state_from_seed(&seed);
+        let mut second = rand_state_from_seed(&seed);
+        let b = Integer::from(10).pow(40);
+        for _ in 0..10 {
+            assert_eq!(random_range(&Integer::ZERO, &b, &mut first), random_range(&Integer::ZERO, &b, &mut second));
+            assert_eq!(random_odd_bits(300, &mut first), random_odd_bits(300, &mut second));
+        }
+        assert_eq!(find_prime_with_bit_length_with_rng(256, 0, &mut first), find_prime_with_bit_length_with_rng(256, 0, &mut second));
+    }
+
+    #[test]
+    fn test_secure_rng() {
+        let seed = Integer::from(987654321);
+        let mut first = SecureRng::from_integer_seed(&seed);
+        let mut second = SecureRng::from_integer_seed(&seed);
+        let mut other = SecureRng::from_integer_seed(&Integer::from(-987654321));
+        let outputs: Vec<u64> = (0..16).map(|_| first.next_u64()).collect();
+        assert_eq!(outputs, (0..16).map(|_| second.next_u64()).collect::<Vec<u64>>());
+        assert_ne!(outputs, (0..16).map(|_| other.next_u64()).collect::<Vec<u64>>());
+
+        second.set_word_pos(6);
+        assert_eq!(second.next_u64(), outputs[3]);
+        assert_ne!(SecureRng::from_seed([0; 32]).next_u32(), SecureRng::from_seed([1; 32]).next_u32());
+
+        // -1 and 511 = 0x01ff must not share a key.
+        let mut minus_one = SecureRng::from_integer_seed(&Integer::from(-1));
+        let mut one = SecureRng::from_integer_seed(&Integer::from(1));
+        let first = minus_one.next_u64();
+        assert_ne!(first, SecureRng::from_integer_seed(&Integer::from(511)).next_u64());
+        assert_ne!(first, one.next_u64());
+        assert_ne!(SecureRng::from_integer_seed(&Integer::ZERO).next_u64(), SecureRng::from_integer_seed(&Integer::from(256)).next_u64());
+
+        let p = find_prime_with_bit_length_with_rng(256, 0, &mut rand_state_from_rng(SecureRng::from_integer_seed(&seed)));
+        let q = find_prime_with_bit_length_with_rng(256, 0, &mut rand_state_from_rng(SecureRng::from_integer_seed(&seed)));
+        assert_eq!(p, q);
+        assert_eq!(p.significant_bits(), 256);
+        assert!(find_prime_with_bit_length_with_rng(128, 0, &mut secure_rand_state()).significant_bits() == 128);
+    }
+
+    #[test]
+    fn test_blum_blum_shub() {
+        assert!(BlumBlumShub::from_primes(Integer::from(13), Integer::from(23), &Integer::from(3)).is_none());
+        assert!(BlumBlumShub::from_primes(Integer::from(11), Integer::from(11), &Integer::from(3)).is_none());
+        assert!(BlumBlumShub::from_primes(Integer::from(11), Integer::from(23), &Integer::from(22)).is_none());
+
+        // x_0 = 9 modulo 253, whose squares are 81, 236, 36, 31, ...
+        let mut generator = BlumBlumShub::from_primes(Integer::from(11), Integer::from(23), &Integer::from(3)).unwrap();
+        let bits: Vec<u32> = (0..4).map(|_| generator.next_bits()).collect();
+        assert_eq!(bits, vec![1, 0, 0, 1]);
+
+        let mut generator = BlumBlumShub::new_with_rng(256, &mut rand_state_from_seed(&Integer::from(5)));
+        assert_eq!(generator.get_modulus().significant_bits(), 256);
+        let bits: Vec<u32> = (0..200).map(|_| generator.next_bits()).collect();
+        generator.seek(&Integer::from(120));
+        assert_eq!(bits[120..], (120..200).map(|_| generator.next_bits()).collect::<Vec<u32>>()[..]);
+
+        let mut generator = generator.with_bits_per_step(8);
+        assert!(generator.next_bits() < 256);
+        let ones: u32 = (0..100).map(|_| generator.next_u32().count_ones()).sum();
+        assert!((1400..1800).contains(&ones));
+
+        let mut state = rand_state_from_rng(BlumBlumShub::new(128));
+        assert!(baillie_psw_is_prime(&find_prime_with_bit_length_with_rng(64, 0, &mut state)));
+    }
+
+    #[test]
+    fn test_blum_micali() {
+        assert!(BlumMicali::from_parameters(Integer::from(21), &Integer::from(2), &Integer::from(3)).is_none());
+        assert!(BlumMicali::from_parameters(Integer::from(23), &Integer::from(5), &Integer::from(23)).is_none());
+
+        // 5 generates the units modulo 23, and from x_0 = 3 the states are 10, 9, 11, 22, 1, 5, ...
+        let mut generator = BlumMicali::from_parameters(Integer::from(23), &Integer::from(5), &Integer::from(3)).unwrap();
+        let bits: Vec<bool> = (0..6).map(|_| generator.next_bit()).collect();
+        assert_eq!(bits, vec![true, true, false, false, true, true]);
+
+        let mut generator = BlumMicali::new_with_rng(128, &mut rand_state_from_seed(&Integer::from(7)));
+        assert_eq!(generator.get_modulus().significant_bits(), 128);
+        let ones: u32 = (0..50).map(|_| generator.next_u32().count_ones()).sum();
+        assert!((600..1000).contains(&ones));
+
+        let mut bytes = [0u8; 7];
+        generator.fill_bytes(&mut bytes);
+        assert!(bytes.iter().any(|b| *b != 0));
+
+        // Equal seeds give the same prime, generator and stream.
+        let mut first = BlumMicali::new_with_rng(64, &mut rand_state_from_seed(&Integer::from(11)));
+        let mut second = BlumMicali::new_with_rng(64, &mut rand_state_from_seed(&Integer::from(11)));
+        assert_eq!(first.get_modulus(), second.get_modulus());
+        assert_eq!((0..4).map(|_| first.next_u32()).collect::<Vec<u32>>(), (0..4).map(|_| second.next_u32()).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_statistical_tests_on_example() {
+        // Example 5.31 of the Handbook of Applied Cryptography.
+        let pattern = "1110001100010001010011101111001001001001";
+        let bits: Vec<bool> = pattern.repeat(4).chars().map(|c| c == '1').collect();
+        assert_eq!(bits.len(), 160);
+        assert!((frequency_test(&bits).statistic - 0.4).abs() < 1e-4);
+        assert!((serial_test(&bits).statistic - 0.6252).abs() < 1e-4);
+        assert!((poker_test(&bits, 3).statistic - 9.6415).abs() < 1e-4);
+        let runs = runs_test(&bits);
+        assert!((runs.statistic - 31.7913).abs() < 1e-4);
+        assert_eq!(runs.degrees_of_freedom, 4);
+        assert_eq!(runs_test(&bits[..79]).degrees_of_freedom, 2);
+        assert!(std::panic::catch_unwind(|| runs_test(&bits[..78])).is_err());
+        assert!(runs.p_value < 0.001);
+        assert!(frequency_test(&bits).passes(0.05));
+        assert_eq!(poker_block_length(160), 3);
+
+        // Quantiles of the chi-square distribution.
+        assert!((chi_square_p_value(3.8415, 1) - 0.05).abs() < 1e-4);
+        assert!((chi_square_p_value(5.9915, 2) - 0.05).abs() < 1e-4);
+        assert!((chi_square_p_value(20.5150, 5) - 0.001).abs() < 1e-5);
+        assert!((chi_square_p_value(124.3421, 100) - 0.05).abs() < 1e-4);
+
+        let uniform = chi_square_test(&[10, 10, 10], &[10., 10., 10.]);
+        assert_eq!(uniform.statistic, 0.);
+        assert_eq!(uniform.p_value, 1.);
+    }
+
+    #[test]
+    fn test_battery_on_generators() {
+        let mut secure = SecureRng::from_integer_seed(&Integer::from(31));
+        let bits = bits_from_rng(&mut secure, 100000);
+        assert!(passes_battery(&bits, 0.001), "{:?}", test_battery(&bits));
+
+        let mut generator = BlumBlumShub::new_with_rng(256, &mut rand_state_from_seed(&Integer::from(31)));
+        assert!(passes_battery(&bits_from_rng(&mut generator, 20000), 0.001));
+
+        // Ones with probability 1/4 instead of 1/2.
+        let biased: Vec<bool> = bits.chunks(2).map(|pair| pair[0] && pair[1]).collect();
+        assert!(!frequency_test(&biased).passes(0.001));
+        assert!(!passes_battery(&biased, 0.001));
+        let alternating: Vec<bool> = (0..10000).map(|i| i % 2 == 0).collect();
+        assert!(frequency_test(&alternating).passes(0.001));
+        assert!(!serial_test(&alternating).passes(0.001));
+        assert!(!runs_test(&alternating).passes(0.001));
+    }
+
+    #[test]
+    fn test_battery_on_random_bits() {
+        let mut rng = rand_state_from_seed(&Integer::from(49));
+        assert!(passes_battery(&bits_from_random_bits_exact(61, 100000, &mut rng), 0.001));
+        assert!(passes_battery(&bits_from_random_odd_bits(62, 100000, &mut rng), 0.001));
+        assert!(passes_battery(&bits_from_random_bits_exact(5, 100000, &mut rng), 0.001));
+        assert!(random_bits_exact_uniformity_test(8, 20000, &mut rng).passes(0.001));
+    }
+
+    // Fresh entropy on every run, so it fails now and then even for a perfect source.
+    #[test]
+    #[ignore]
+    fn test_battery_on_randint_bits() {
+        assert!(passes_battery(&bits_from_randint_bits(61, 100000), 0.0001));
+        assert!(passes_battery(&bits_from_randint_bits_odd(62, 100000), 0.0001));
+        assert!(passes_battery(&bits_from_randint_bits(5, 100000), 0.0001));
+        assert!(randint_bits_uniformity_test(8, 20000).passes(0.0001));
+    }
+}
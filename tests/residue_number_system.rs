@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod residue_number_system {
+    use beralg::integers::residue_number_system::*;
+    use rug::{Integer, Complete, rand::RandState};
+    use std::sync::Arc;
+
+    fn random_basis(rng: &mut RandState, channels: usize) -> Arc<ResidueBasis> {
+        let mut p: Integer = Integer::from(Integer::random_bits(40, rng)).next_prime();
+        let mut moduli: Vec<Integer> = Vec::with_capacity(channels);
+        for _ in 0..channels {
+            moduli.push(p.clone());
+            p.next_prime_mut();
+        }
+        Arc::new(ResidueBasis::new(moduli).unwrap())
+    }
+
+    #[test]
+    fn test_basis_requires_coprime_moduli() {
+        assert!(ResidueBasis::new(vec![Integer::from(6), Integer::from(35), Integer::from(11)]).is_some());
+        assert!(ResidueBasis::new(vec![Integer::from(6), Integer::from(35), Integer::from(21)]).is_none());
+        assert!(ResidueBasis::new(vec![Integer::from(1), Integer::from(7)]).is_none());
+        assert!(ResidueBasis::new(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_equality_depends_on_basis() {
+        let basis = Arc::new(ResidueBasis::new(vec![Integer::from(5), Integer::from(7)]).unwrap());
+        let copy = Arc::new(ResidueBasis::new(vec![Integer::from(5), Integer::from(7)]).unwrap());
+        let other = Arc::new(ResidueBasis::new(vec![Integer::from(5), Integer::from(9)]).unwrap());
+        let residues = vec![Integer::from(1), Integer::from(2)];
+
+        let a = ResidueNumber::from_residues(basis.clone(), residues.clone()).unwrap();
+        assert_eq!(a, ResidueNumber::new(basis, &Integer::from(16)));
+        assert_eq!(a, ResidueNumber::from_residues(copy, residues.clone()).unwrap());
+        assert_ne!(a, ResidueNumber::from_residues(other, residues).unwrap());
+    }
+
+    #[test]
+    fn test_residue_arithmetic() {
+        let mut rng = RandState::new();
+        let loops = 100;
+
+        for i in 0..loops {
+            let basis = random_basis(&mut rng, 1 + i % 10);
+            let m = basis.get_product().clone();
+
+            let a: Integer = Integer::from(Integer::random_bits(400, &mut rng));
+            let b: Integer = Integer::from(Integer::random_bits(400, &mut rng));
+            let a_rns = ResidueNumber::new(basis.clone(), &a);
+            let b_rns = ResidueNumber::new(basis.clone(), &b);
+
+            let sum = a_rns.add_ref(&b_rns);
+            let difference = a_rns.sub_ref(&b_rns);
+            let product = a_rns.mul_ref(&b_rns);
+
+            assert_eq!(sum.to_integer_crt(), (&a + &b).complete().modulo(&m));
+            assert_eq!(sum.to_integer_mixed_radix(), (&a + &b).complete().modulo(&m));
+            assert_eq!(difference.to_integer_crt(), (&a - &b).complete().modulo(&m));
+            assert_eq!(difference.to_integer_mixed_radix(), (&a - &b).complete().modulo(&m));
+            assert_eq!(product.to_integer_crt(), (&a * &b).complete().modulo(&m));
+            assert_eq!(product.to_integer_mixed_radix(), (&a * &b).complete().modulo(&m));
+        }
+    }
+
+    #[test]
+    fn test_signed_reconstruction() {
+        let mut rng = RandState::new();
+        let basis = random_basis(&mut rng, 8);
+
+        for _ in 0..100 {
+            let a: Integer = Integer::from(Integer::random_bits(150, &mut rng)) - Integer::from(Integer::random_bits(150, &mut rng));
+            let b: Integer = Integer::from(Integer::random_bits(150, &mut rng)) - Integer::from(Integer::random_bits(150, &mut rng));
+            let product = ResidueNumber::new(basis.clone(), &a).mul_ref(&ResidueNumber::new(basis.clone(), &b));
+            assert_eq!(product.to_integer_signed(), (&a * &b).complete());
+        }
+    }
+}
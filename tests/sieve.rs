@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod sieve {
+    use beralg::integers::sieve::*;
+    use rug::{Integer, integer::IsPrime, rand::RandState};
+
+    fn rug_primes(a: u64, b: u64) -> Vec<u64> {
+        let mut primes: Vec<u64> = Vec::new();
+        let mut p: Integer = Integer::from(a) - 1u32;
+        p.next_prime_mut();
+        while p < b {
+            primes.push(p.to_u64().unwrap());
+            p.next_prime_mut();
+        }
+        primes
+    }
+
+    #[test]
+    fn test_primes_in_range() {
+        assert_eq!(primes_in_range(0, 1000).collect::<Vec<u64>>(), rug_primes(0, 1000));
+        assert_eq!(primes_in_range(2, 3).collect::<Vec<u64>>(), vec![2]);
+        assert_eq!(primes_in_range(24, 29).count(), 0);
+        assert_eq!(primes_in_range(100, 10).count(), 0);
+
+        // Crosses several segments above the prime table.
+        let a: u64 = 1_000_000_000_000;
+        let b: u64 = a + 300_000;
+        assert_eq!(primes_in_range(a, b).collect::<Vec<u64>>(), rug_primes(a, b));
+        assert_eq!(count_primes_in_range(a, b), rug_primes(a, b).len() as u64);
+
+        let top: u64 = 1 << 50;
+        assert_eq!(primes_in_range(top - 1000, top).collect::<Vec<u64>>(), rug_primes(top - 1000, top));
+    }
+
+    #[test]
+    fn test_prime_pi_and_nth_prime() {
+        let values: [(u64, u64); 7] = [(0, 0), (1, 0), (2, 1), (100, 25), (10_000_000, 664579), (10_000_019, 664580), (20_000_000, 1270607)];
+        for (x, pi) in values {
+            assert_eq!(prime_pi(x), pi, "Wrong count of primes up to {}", x);
+        }
+
+        assert_eq!(nth_prime(0), None);
+        assert_eq!(nth_prime(1), Some(2));
+        assert_eq!(nth_prime(25), Some(97));
+        assert_eq!(nth_prime(664579), Some(9999991));
+        assert_eq!(nth_prime(664580), Some(10000019));
+        assert_eq!(nth_prime(1_000_000), Some(15485863));
+    }
+
+    #[test]
+    fn test_next_and_prev_prime() {
+        assert_eq!(next_prime(&Integer::from(-5)), 2);
+        assert_eq!(next_prime(&Integer::from(2)), 3);
+        assert_eq!(next_prime(&Integer::from(9999991)), 10000019);
+        assert_eq!(prev_prime(&Integer::from(2)), None);
+        assert_eq!(prev_prime(&Integer::from(3)), Some(Integer::from(2)));
+        assert_eq!(prev_prime(&Integer::from(10000019)), Some(Integer::from(9999991)));
+        assert_eq!(prev_prime(&Integer::from(10000020)), Some(Integer::from(10000019)));
+
+        let mut rng = RandState::new();
+        for bits in [20, 40, 64, 100, 300] {
+            for _ in 0..20 {
+                let n = Integer::from(Integer::random_bits(bits, &mut rng));
+                let next = next_prime(&n);
+                assert_eq!(next, n.clone().next_prime(), "Wrong next prime after {}", n);
+                if let Some(prev) = prev_prime(&n) {
+                    assert!(prev < n && prev.is_probably_prime(30) != IsPrime::No, "{} is not a prime below {}", prev, n);
+                    assert!(prev.clone().next_prime() >= n, "Skipped a prime between {} and {}", prev, n);
+                }
+            }
+        }
+    }
+}